@@ -0,0 +1,25 @@
+// Starts nooblang-dap from the nooblang.dapPath setting, which VS Code
+// can't do from package.json alone: a "program" there is taken as a file
+// inside the extension
+const vscode = require("vscode");
+
+function activate(context) {
+    context.subscriptions.push(
+        vscode.debug.registerDebugAdapterDescriptorFactory("nooblang", {
+            createDebugAdapterDescriptor(session) {
+                const path = vscode.workspace
+                    .getConfiguration("nooblang", session.workspaceFolder)
+                    .get("dapPath", "nooblang-dap");
+                const folder = session.workspaceFolder;
+                const resolved = folder
+                    ? path.replace("${workspaceFolder}", folder.uri.fsPath)
+                    : path;
+                return new vscode.DebugAdapterExecutable(resolved);
+            },
+        })
+    );
+}
+
+function deactivate() {}
+
+module.exports = { activate, deactivate };
@@ -1,6 +1,6 @@
-use crate::lexer::Pos;
+use crate::{lexer::Span, ratio::Ratio};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum Operator {
     Is,
     Isnt,
@@ -15,6 +15,22 @@ pub enum Operator {
     Mod,
     ToThe,
     Neg,
+    Div,
+    Min,
+    Max,
+    Floor,
+    Ceil,
+    Round,
+    Abs,
+    Sqrt,
+    Length,
+    Letter,
+    Part,
+    Find,
+    Upper,
+    Lower,
+    Code,
+    Character,
     Read,
     And,
     Or,
@@ -24,19 +40,22 @@ pub enum Operator {
     Choice,
 }
 
-// Abstract types
+// Abstract types, each with the code it came from
 #[derive(Debug)]
 pub enum Stmt {
     // Id, Expr
-    Asgn(Box<Expr>, Box<Expr>, usize),
+    Asgn(Box<Expr>, Box<Expr>, Span),
 
     // From, To
-    RunFrom(Box<Expr>, Box<Expr>, usize),
-    RunAt(Box<Expr>, usize),
-    Write(Box<Expr>, usize),
+    RunFrom(Box<Expr>, Box<Expr>, Span),
+    RunAt(Box<Expr>, Span),
+    Write(Box<Expr>, Span),
 
-    // Statement, condition
-    Switch(Box<Stmt>, Box<Expr>, usize),
+    // 'run' to repeat (or the last one), times
+    Repeat(Option<Box<Stmt>>, Box<Expr>, Span),
+
+    // Statement, condition, statement if it's not a yes
+    Switch(Box<Stmt>, Box<Expr>, Option<Box<Stmt>>, Span),
 
     Expr(Box<Expr>),
 
@@ -48,17 +67,39 @@ pub enum Stmt {
 
 #[derive(Debug)]
 pub enum Expr {
-    Unary(Operator, Box<Expr>, Pos),
-    Binary(Operator, Box<Expr>, Box<Expr>, Pos),
-    Nullary(Operator, Pos),
-    Literal(Literal, Pos),
-    Id(String, Pos),
+    Unary(Operator, Box<Expr>, Span),
+    Binary(Operator, Box<Expr>, Box<Expr>, Span),
+    // 'part A to B of X' is Part, A, B, X
+    Ternary(Operator, Box<Expr>, Box<Expr>, Box<Expr>, Span),
+    Nullary(Operator, Span),
+    Literal(Literal, Span),
+    Id(String, Span),
+}
+
+impl Expr {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Unary(.., span)
+            | Self::Binary(.., span)
+            | Self::Ternary(.., span)
+            | Self::Nullary(_, span)
+            | Self::Literal(_, span)
+            | Self::Id(_, span) => *span,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub enum Literal {
     Integer(u128),
-    Decimal(f64),
+    #[cfg(feature = "bigint")]
+    Big(crate::bigint::Big),
+    // 'dot', exact
+    Decimal(Ratio),
+    // 'dot' with too many digits to be exact
+    Float(f64),
     Text(String),
     Choice(bool),
+    // Name, line it's on
+    Label(String, usize),
 }
@@ -0,0 +1,335 @@
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+    ops::{Add, Div, Mul, Neg, Rem, Sub},
+};
+
+// Whole numbers of any size, for when i128 isn't enough
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Big {
+    neg: bool,
+    // Base 2^32, least significant first, no zeros at the end. 0 is empty.
+    mag: Vec<u32>,
+}
+
+// Biggest power of 10 in a u32, for printing and parsing
+const CHUNK: u32 = 1_000_000_000;
+const CHUNK_DIGITS: usize = 9;
+
+impl Big {
+    fn new(neg: bool, mut mag: Vec<u32>) -> Self {
+        while mag.last() == Some(&0) {
+            mag.pop();
+        }
+        // No -0
+        let neg = neg && !mag.is_empty();
+        Self { neg, mag }
+    }
+
+    // Decimal digits, maybe after a -
+    pub fn parse(digits: &str) -> Option<Self> {
+        if let Some(digits) = digits.strip_prefix('-') {
+            return Self::parse(digits).map(|n| -&n);
+        }
+        if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let mut mag = Vec::new();
+        let first = digits.len() % CHUNK_DIGITS;
+        let chunks = std::iter::once(&digits[..first])
+            .filter(|s| !s.is_empty())
+            .chain(
+                digits.as_bytes()[first..]
+                    .chunks(CHUNK_DIGITS)
+                    .map(|c| std::str::from_utf8(c).unwrap_or("0")),
+            );
+        for chunk in chunks {
+            let mut carry = chunk.parse::<u32>().ok()? as u64;
+            // mag = mag * 10^9 + chunk
+            for d in mag.iter_mut() {
+                let v = *d as u64 * CHUNK as u64 + carry;
+                *d = v as u32;
+                carry = v >> 32;
+            }
+            if carry > 0 {
+                mag.push(carry as u32);
+            }
+        }
+        Some(Self::new(false, mag))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mag.is_empty()
+    }
+
+    pub fn to_i128(&self) -> Option<i128> {
+        if self.mag.len() > 4 {
+            return None;
+        }
+        let mag = self
+            .mag
+            .iter()
+            .rev()
+            .fold(0u128, |acc, d| (acc << 32) | *d as u128);
+        if self.neg {
+            0i128.checked_sub_unsigned(mag)
+        } else {
+            i128::try_from(mag).ok()
+        }
+    }
+
+    // Closest float, infinite if it's too big for one
+    pub fn to_f64(&self) -> f64 {
+        let mag = self
+            .mag
+            .iter()
+            .rev()
+            .fold(0.0, |acc, d| acc * 4294967296.0 + *d as f64);
+        if self.neg {
+            -mag
+        } else {
+            mag
+        }
+    }
+
+    // Number of bits in the magnitude
+    pub fn bits(&self) -> u64 {
+        match self.mag.last() {
+            Some(top) => self.mag.len() as u64 * 32 - top.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    pub fn pow(&self, mut exp: u32) -> Self {
+        let mut base = self.clone();
+        let mut result = Big::from(1);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = &result * &base;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+
+    // Whole part of the square root, self has to be 0 or more
+    pub fn sqrt(&self) -> Self {
+        if self.is_zero() {
+            return self.clone();
+        }
+        // Start above the root and come down until it stops shrinking
+        let two = Big::from(2);
+        let mut root = two.pow((self.bits() / 2 + 1) as u32);
+        loop {
+            let next = &(&root + &(self / &root)) / &two;
+            if next >= root {
+                return root;
+            }
+            root = next;
+        }
+    }
+
+    fn cmp_mag(a: &[u32], b: &[u32]) -> Ordering {
+        a.len()
+            .cmp(&b.len())
+            .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+    }
+
+    fn add_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+        let mut sum = Vec::with_capacity(a.len().max(b.len()) + 1);
+        let mut carry = 0u64;
+        for i in 0..a.len().max(b.len()) {
+            let v = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+            sum.push(v as u32);
+            carry = v >> 32;
+        }
+        sum.push(carry as u32);
+        sum
+    }
+
+    // a - b, a has to be the bigger one
+    fn sub_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+        let mut diff = Vec::with_capacity(a.len());
+        let mut borrow = 0i64;
+        for (i, d) in a.iter().enumerate() {
+            let mut v = *d as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+            borrow = 0;
+            if v < 0 {
+                v += 1 << 32;
+                borrow = 1;
+            }
+            diff.push(v as u32);
+        }
+        diff
+    }
+
+    // Magnitude divided by a small number, and what's left
+    fn div_small(mag: &[u32], by: u32) -> (Vec<u32>, u32) {
+        let mut quot = vec![0; mag.len()];
+        let mut rem = 0u64;
+        for (i, d) in mag.iter().enumerate().rev() {
+            let v = (rem << 32) | *d as u64;
+            quot[i] = (v / by as u64) as u32;
+            rem = v % by as u64;
+        }
+        (quot, rem as u32)
+    }
+
+    // a divided by b and what's left, one bit at a time
+    fn div_rem_mag(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+        let mut quot = vec![0; a.len()];
+        let mut rem: Vec<u32> = Vec::new();
+        for i in (0..a.len() * 32).rev() {
+            // rem = rem * 2 + bit i of a
+            let mut carry = (a[i / 32] >> (i % 32)) & 1;
+            for d in rem.iter_mut() {
+                let top = *d >> 31;
+                *d = (*d << 1) | carry;
+                carry = top;
+            }
+            if carry > 0 {
+                rem.push(carry);
+            }
+            if Self::cmp_mag(&rem, b) != Ordering::Less {
+                rem = Self::sub_mag(&rem, b);
+                while rem.last() == Some(&0) {
+                    rem.pop();
+                }
+                quot[i / 32] |= 1 << (i % 32);
+            }
+        }
+        (quot, rem)
+    }
+}
+
+impl From<i128> for Big {
+    fn from(n: i128) -> Self {
+        let mut mag = n.unsigned_abs();
+        let mut digits = Vec::new();
+        while mag > 0 {
+            digits.push(mag as u32);
+            mag >>= 32;
+        }
+        Self::new(n < 0, digits)
+    }
+}
+
+impl Ord for Big {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.neg, other.neg) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => Self::cmp_mag(&self.mag, &other.mag),
+            (true, true) => Self::cmp_mag(&other.mag, &self.mag),
+        }
+    }
+}
+
+impl PartialOrd for Big {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for &Big {
+    type Output = Big;
+
+    fn add(self, other: &Big) -> Big {
+        if self.neg == other.neg {
+            return Big::new(self.neg, Big::add_mag(&self.mag, &other.mag));
+        }
+        // Different signs, the bigger one wins
+        match Big::cmp_mag(&self.mag, &other.mag) {
+            Ordering::Less => Big::new(other.neg, Big::sub_mag(&other.mag, &self.mag)),
+            _ => Big::new(self.neg, Big::sub_mag(&self.mag, &other.mag)),
+        }
+    }
+}
+
+impl Sub for &Big {
+    type Output = Big;
+
+    fn sub(self, other: &Big) -> Big {
+        self + &-other
+    }
+}
+
+impl Mul for &Big {
+    type Output = Big;
+
+    fn mul(self, other: &Big) -> Big {
+        let mut prod = vec![0u32; self.mag.len() + other.mag.len()];
+        for (i, a) in self.mag.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, b) in other.mag.iter().enumerate() {
+                let v = *a as u64 * *b as u64 + prod[i + j] as u64 + carry;
+                prod[i + j] = v as u32;
+                carry = v >> 32;
+            }
+            prod[i + other.mag.len()] = carry as u32;
+        }
+        Big::new(self.neg != other.neg, prod)
+    }
+}
+
+// Same sign as self, like % on i128. Panics on 0 like it too.
+impl Rem for &Big {
+    type Output = Big;
+
+    fn rem(self, other: &Big) -> Big {
+        assert!(!other.is_zero(), "attempt to calculate the remainder with a divisor of zero");
+        Big::new(self.neg, Big::div_rem_mag(&self.mag, &other.mag).1)
+    }
+}
+
+// Rounds towards 0 like / on i128. Panics on 0 like it too.
+impl Div for &Big {
+    type Output = Big;
+
+    fn div(self, other: &Big) -> Big {
+        assert!(!other.is_zero(), "attempt to divide by zero");
+        Big::new(self.neg != other.neg, Big::div_rem_mag(&self.mag, &other.mag).0)
+    }
+}
+
+impl Neg for &Big {
+    type Output = Big;
+
+    fn neg(self) -> Big {
+        Big::new(!self.neg, self.mag.clone())
+    }
+}
+
+impl Display for Big {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        // Nine digits at a time, least significant first
+        let mut chunks = Vec::new();
+        let mut mag = self.mag.clone();
+        while !mag.is_empty() {
+            let (quot, rem) = Big::div_small(&mag, CHUNK);
+            chunks.push(rem);
+            mag = quot;
+            while mag.last() == Some(&0) {
+                mag.pop();
+            }
+        }
+
+        if self.neg {
+            write!(f, "-")?;
+        }
+        let mut chunks = chunks.iter().rev();
+        if let Some(top) = chunks.next() {
+            write!(f, "{}", top)?;
+        }
+        for chunk in chunks {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
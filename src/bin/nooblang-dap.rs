@@ -0,0 +1,12 @@
+use std::{
+    io::{stdin, stdout},
+    process::exit,
+};
+
+// Debug Adapter Protocol server on stdio, for editors
+fn main() {
+    if let Err(e) = nooblang_rust::dap::serve(stdin().lock(), stdout()) {
+        eprintln!("{}", e);
+        exit(1);
+    }
+}
@@ -0,0 +1,12 @@
+use std::{
+    io::{stdin, stdout},
+    process::exit,
+};
+
+// Language Server Protocol server on stdio, for editors
+fn main() {
+    if let Err(e) = nooblang_rust::lsp::serve(stdin().lock(), stdout()) {
+        eprintln!("{}", e);
+        exit(1);
+    }
+}
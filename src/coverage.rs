@@ -0,0 +1,242 @@
+use std::{collections::BTreeMap, fmt::Write, fs, path::Path};
+
+use crate::{
+    ast::Stmt,
+    errors::Errors,
+    eval::{Eval, Hook},
+    lexer::{Pos, Span},
+};
+
+// Which lines and 'if's the runs of a program reached, written out as lcov
+// so coverage viewers can show it. Reading the file back in lets separate runs
+// add up in it.
+
+// One program, over one run or more
+#[derive(Debug, Clone)]
+pub struct FileCoverage {
+    // Line n is lines[n - 1], None for lines with nothing to run
+    lines: Vec<Option<u64>>,
+    // The 'if's on each line, left to right, by where their condition
+    // starts: (start, times yes, times no)
+    ifs: Vec<Vec<(Pos, u64, u64)>>,
+}
+
+impl FileCoverage {
+    pub fn new(prog: &[Stmt]) -> Self {
+        let mut ifs = vec![Vec::new(); prog.len()];
+        for (i, stmt) in prog.iter().enumerate() {
+            conditions(stmt, &mut ifs[i]);
+        }
+        Self {
+            lines: prog
+                .iter()
+                .map(|stmt| match stmt {
+                    Stmt::Blank => None,
+                    _ => Some(0),
+                })
+                .collect(),
+            ifs,
+        }
+    }
+
+    // Adds the counts of another run of the same program. If the program
+    // changed in between, only what's in both adds up.
+    pub fn merge(&mut self, other: &FileCoverage) {
+        for (line, hits) in self.lines.iter_mut().zip(&other.lines) {
+            if let (Some(line), Some(hits)) = (line, hits) {
+                *line += hits;
+            }
+        }
+        for (ifs, other) in self.ifs.iter_mut().zip(&other.ifs) {
+            for ((_, yes, no), (_, other_yes, other_no)) in ifs.iter_mut().zip(other) {
+                *yes += other_yes;
+                *no += other_no;
+            }
+        }
+    }
+
+    // lcov's record for one source file
+    pub fn to_lcov(&self, path: &str) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "SF:{}", path);
+
+        let (mut found, mut hit) = (0, 0);
+        for (i, ifs) in self.ifs.iter().enumerate() {
+            for (block, (_, yes, no)) in ifs.iter().enumerate() {
+                for (branch, taken) in [yes, no].into_iter().enumerate() {
+                    // Never checked, so neither way was taken
+                    let taken = match yes + no {
+                        0 => "-".to_string(),
+                        _ => taken.to_string(),
+                    };
+                    let _ = writeln!(out, "BRDA:{},{},{},{}", i + 1, block, branch, taken);
+                }
+                found += 2;
+                hit += (*yes > 0) as usize + (*no > 0) as usize;
+            }
+        }
+        let _ = writeln!(out, "BRF:{}", found);
+        let _ = writeln!(out, "BRH:{}", hit);
+
+        let (mut found, mut hit) = (0, 0);
+        for (i, hits) in self.lines.iter().enumerate() {
+            if let Some(hits) = hits {
+                let _ = writeln!(out, "DA:{},{}", i + 1, hits);
+                found += 1;
+                hit += (*hits > 0) as usize;
+            }
+        }
+        let _ = writeln!(out, "LF:{}", found);
+        let _ = writeln!(out, "LH:{}", hit);
+        out.push_str("end_of_record\n");
+        out
+    }
+}
+
+// One record read back from lcov. It doesn't say where conditions start, so
+// the 'if's go by their order in the line.
+fn from_lcov(record: &[&str]) -> Option<FileCoverage> {
+    let mut file = FileCoverage {
+        lines: Vec::new(),
+        ifs: Vec::new(),
+    };
+    for line in record {
+        if let Some(da) = line.strip_prefix("DA:") {
+            let mut fields = da.split(',');
+            let line: usize = fields.next()?.parse().ok()?;
+            let hits = fields.next()?.parse().ok()?;
+            if line == 0 {
+                return None;
+            }
+            if file.lines.len() < line {
+                file.lines.resize(line, None);
+            }
+            file.lines[line - 1] = Some(hits);
+        } else if let Some(brda) = line.strip_prefix("BRDA:") {
+            let [line, block, branch, taken] = brda.split(',').collect::<Vec<_>>()[..] else {
+                return None;
+            };
+            let line: usize = line.parse().ok()?;
+            let block: usize = block.parse().ok()?;
+            let taken = match taken {
+                "-" => 0,
+                taken => taken.parse().ok()?,
+            };
+            if line == 0 {
+                return None;
+            }
+            if file.ifs.len() < line {
+                file.ifs.resize(line, Vec::new());
+            }
+            let ifs = &mut file.ifs[line - 1];
+            if ifs.len() <= block {
+                ifs.resize(block + 1, (Pos(line, 0), 0, 0));
+            }
+            match branch {
+                "0" => ifs[block].1 = taken,
+                "1" => ifs[block].2 = taken,
+                _ => return None,
+            }
+        }
+    }
+    Some(file)
+}
+
+// Where the conditions of the 'if's in a line start, left to right
+fn conditions(stmt: &Stmt, found: &mut Vec<(Pos, u64, u64)>) {
+    if let Stmt::Switch(stmt, cond, otherwise, _) = stmt {
+        conditions(stmt, found);
+        found.push((cond.span().0, 0, 0));
+        if let Some(otherwise) = otherwise {
+            conditions(otherwise, found);
+        }
+    }
+}
+
+impl Hook for FileCoverage {
+    fn line(&mut self, _eval: &mut Eval, line: usize) -> Result<(), Errors> {
+        if let Some(Some(hits)) = self.lines.get_mut(line - 1) {
+            *hits += 1;
+        }
+        Ok(())
+    }
+
+    fn switch(&mut self, cond: Span, yes: bool) {
+        let ifs = &mut self.ifs[cond.line() - 1];
+        if let Some((_, yes_count, no_count)) = ifs.iter_mut().find(|(at, ..)| *at == cond.0) {
+            match yes {
+                true => *yes_count += 1,
+                false => *no_count += 1,
+            }
+        }
+    }
+}
+
+// Every program that ran, by path
+#[derive(Debug, Default)]
+pub struct Coverage {
+    files: BTreeMap<String, FileCoverage>,
+}
+
+impl Coverage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // What an lcov file already has, nothing if it isn't there yet
+    pub fn load(path: &Path) -> Result<Self, Errors> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::new()),
+            Err(_) => return Err(Errors::IOError),
+        };
+        Self::from_lcov(&text).ok_or_else(|| {
+            Errors::ArgumentError(format!("CAN'T READ {} AS LCOV", path.display()))
+        })
+    }
+
+    // Only what nooblang writes is kept, other lines in a record are skipped
+    pub fn from_lcov(text: &str) -> Option<Self> {
+        let mut coverage = Self::new();
+        let mut path = None;
+        let mut record = Vec::new();
+        for line in text.lines().map(str::trim) {
+            if let Some(file) = line.strip_prefix("SF:") {
+                path = Some(file);
+                record.clear();
+            } else if line == "end_of_record" {
+                coverage.add(path.take()?, from_lcov(&record)?);
+            } else if path.is_some() {
+                record.push(line);
+            } else if !line.is_empty() && !line.starts_with("TN:") {
+                return None;
+            }
+        }
+        // Cut off in the middle of a record
+        match path {
+            Some(_) => None,
+            None => Some(coverage),
+        }
+    }
+
+    // The same path again adds to what's there, keeping the newest layout
+    pub fn add(&mut self, path: &str, mut file: FileCoverage) {
+        if let Some(old) = self.files.remove(path) {
+            file.merge(&old);
+        }
+        self.files.insert(path.to_string(), file);
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Errors> {
+        fs::write(path, self.to_lcov()).map_err(|_| Errors::IOError)
+    }
+
+    pub fn to_lcov(&self) -> String {
+        let mut out = String::new();
+        for (path, file) in &self.files {
+            out.push_str("TN:\n");
+            out.push_str(&file.to_lcov(path));
+        }
+        out
+    }
+}
@@ -0,0 +1,429 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeSet,
+    fs,
+    io::{BufRead, Write},
+    rc::Rc,
+};
+
+use crate::{
+    debugger::{Debugger, Mode},
+    errors::Errors,
+    eval::{Buffer, Eval, Hook, Io},
+    json::{self, Json, Message},
+    lexer::Reader,
+};
+
+// Debug Adapter Protocol over any pair of streams, stdio for editors.
+// Everything is one thread: the program runs inside the 'launch', and
+// requests are only read again when it stops at a line or finishes.
+
+struct Session<'a> {
+    input: Box<dyn BufRead + 'a>,
+    output: Box<dyn Write + 'a>,
+    seq: usize,
+
+    program: Option<String>,
+    // What 'read' gets, stdin is taken by the protocol
+    program_input: Option<String>,
+    stop_on_entry: bool,
+    configured: bool,
+    breakpoints: BTreeSet<usize>,
+    mode: Mode,
+    // The editor left while the program was stopped
+    disconnected: bool,
+}
+
+enum Action {
+    Stay,
+    Resume,
+    Disconnect,
+}
+
+impl<'a> Session<'a> {
+    // DAP has no way to answer what can't be read, so it's skipped
+    fn recv(&mut self) -> Result<Option<Json>, Errors> {
+        loop {
+            match json::read_message(&mut self.input)? {
+                Some(Message::Json(message)) => return Ok(Some(message)),
+                Some(Message::Garbled) => (),
+                None => return Ok(None),
+            }
+        }
+    }
+
+    fn send(&mut self, kind: &str, mut fields: Vec<(&str, Json)>) -> Result<(), Errors> {
+        self.seq += 1;
+        fields.insert(0, ("seq", self.seq.into()));
+        fields.insert(1, ("type", kind.into()));
+        json::write_message(&mut self.output, &Json::object(fields))
+    }
+
+    fn respond(&mut self, request: &Json, body: Result<Json, String>) -> Result<(), Errors> {
+        let mut fields = vec![
+            ("request_seq", request.get("seq").clone()),
+            ("command", request.get("command").clone()),
+            ("success", body.is_ok().into()),
+        ];
+        match body {
+            Ok(body) => fields.push(("body", body)),
+            Err(msg) => fields.push(("message", msg.into())),
+        }
+        self.send("response", fields)
+    }
+
+    fn event(&mut self, event: &str, body: Json) -> Result<(), Errors> {
+        self.send("event", vec![("event", event.into()), ("body", body)])
+    }
+}
+
+type Shared<'a> = Rc<RefCell<Session<'a>>>;
+
+pub fn serve<'a>(input: impl BufRead + 'a, output: impl Write + 'a) -> Result<(), Errors> {
+    let session = Rc::new(RefCell::new(Session {
+        input: Box::new(input),
+        output: Box::new(output),
+        seq: 0,
+        program: None,
+        program_input: None,
+        stop_on_entry: false,
+        configured: false,
+        breakpoints: BTreeSet::new(),
+        mode: Mode::Continue,
+        disconnected: false,
+    }));
+
+    loop {
+        let Some(request) = session.borrow_mut().recv()? else {
+            return Ok(());
+        };
+        if let Action::Disconnect = handle(&session, &request, None)? {
+            return Ok(());
+        }
+
+        // Both are needed before the program can start, in either order
+        let ready = {
+            let s = session.borrow();
+            s.configured && s.program.is_some()
+        };
+        if ready {
+            if launch(&session)? {
+                return Ok(());
+            }
+            let mut s = session.borrow_mut();
+            s.program = None;
+            s.configured = false;
+        }
+    }
+}
+
+// Run the program to the end, true if the editor disconnected meanwhile
+fn launch(session: &Shared) -> Result<bool, Errors> {
+    let path = session.borrow().program.clone().unwrap_or_default();
+    let stop_on_entry = session.borrow().stop_on_entry;
+    let input = session.borrow().program_input.as_deref().map(Buffer::new);
+    session.borrow_mut().mode = if stop_on_entry {
+        Mode::Step
+    } else {
+        Mode::Continue
+    };
+
+    let result = fs::read(&path)
+        .map_err(|_| Errors::IOError)
+        .and_then(|source| {
+            let mut reader = Reader::from(source);
+            let ast = crate::parse(&mut reader)?;
+
+            let mut eval = Eval::new(&ast, &reader);
+            eval.set_io(Box::new(Output {
+                session: session.clone(),
+                input,
+            }));
+            eval.set_hook(Box::new(Adapter {
+                session: session.clone(),
+                entry: stop_on_entry,
+            }));
+            eval.run_prog()
+        });
+
+    let mut s = session.borrow_mut();
+    let disconnected = s.disconnected;
+    let code = match result {
+        Ok(_) => 0,
+        Err(e) => {
+            s.event(
+                "output",
+                Json::object(vec![
+                    ("category", "stderr".into()),
+                    ("output", format!("{}\n", e).into()),
+                ]),
+            )?;
+            1
+        }
+    };
+    if !disconnected {
+        s.event("exited", Json::object(vec![("exitCode", code.into())]))?;
+        s.event("terminated", Json::object(vec![]))?;
+    }
+    Ok(disconnected)
+}
+
+fn handle(
+    session: &Shared,
+    request: &Json,
+    mut stopped: Option<(&mut Eval, usize)>,
+) -> Result<Action, Errors> {
+    let args = request.get("arguments");
+    let mut action = Action::Stay;
+    let depth = stopped
+        .as_ref()
+        .map_or(0, |(eval, _)| Debugger::depth(eval));
+
+    let body = match request.get("command").as_str().unwrap_or_default() {
+        "initialize" => {
+            session.borrow_mut().respond(
+                request,
+                Ok(Json::object(vec![
+                    ("supportsConfigurationDoneRequest", true.into()),
+                    ("supportsEvaluateForHovers", true.into()),
+                    ("supportsSetVariable", true.into()),
+                ])),
+            )?;
+            return session
+                .borrow_mut()
+                .event("initialized", Json::object(vec![]))
+                .map(|_| Action::Stay);
+        }
+        "launch" => {
+            let mut s = session.borrow_mut();
+            match args.get("program").as_str() {
+                Some(program) => {
+                    s.program = Some(program.to_string());
+                    s.program_input = args.get("input").as_str().map(str::to_string);
+                    s.stop_on_entry = args.get("stopOnEntry").as_bool().unwrap_or(false);
+                    Ok(Json::object(vec![]))
+                }
+                None => Err("NEED program TO RUN".to_string()),
+            }
+        }
+        "configurationDone" => {
+            session.borrow_mut().configured = true;
+            Ok(Json::object(vec![]))
+        }
+        "setBreakpoints" => {
+            let lines: Vec<usize> = args
+                .get("breakpoints")
+                .as_array()
+                .iter()
+                .filter_map(|b| b.get("line").as_usize())
+                .collect();
+            session.borrow_mut().breakpoints = lines.iter().copied().collect();
+            let breakpoints = lines
+                .iter()
+                .map(|line| Json::object(vec![("verified", true.into()), ("line", (*line).into())]))
+                .collect::<Vec<_>>();
+            Ok(Json::object(vec![("breakpoints", breakpoints.into())]))
+        }
+        "threads" => Ok(Json::object(vec![(
+            "threads",
+            vec![Json::object(vec![
+                ("id", 1.into()),
+                ("name", "main".into()),
+            ])]
+            .into(),
+        )])),
+        "stackTrace" => {
+            let frames = match &stopped {
+                Some((eval, line)) => stack_frames(eval, *line, session),
+                None => Vec::new(),
+            };
+            Ok(Json::object(vec![
+                ("totalFrames", frames.len().into()),
+                ("stackFrames", frames.into()),
+            ]))
+        }
+        "scopes" => Ok(Json::object(vec![(
+            "scopes",
+            vec![Json::object(vec![
+                ("name", "Variables".into()),
+                ("variablesReference", 1.into()),
+                ("expensive", false.into()),
+            ])]
+            .into(),
+        )])),
+        "variables" => {
+            let mut vars = Vec::new();
+            if let Some((eval, _)) = &stopped {
+                let mut all: Vec<_> = eval.vars().collect();
+                all.sort_by(|a, b| a.0.cmp(b.0));
+                for (name, value) in all {
+                    vars.push(Json::object(vec![
+                        ("name", name.into()),
+                        ("value", Eval::display(value).into()),
+                        ("variablesReference", 0.into()),
+                    ]));
+                }
+            }
+            Ok(Json::object(vec![("variables", vars.into())]))
+        }
+        "evaluate" => match &mut stopped {
+            Some((eval, _)) => {
+                let expr = args.get("expression").as_str().unwrap_or_default();
+                match Debugger::eval_line(eval, expr) {
+                    Ok(v) => Ok(Json::object(vec![
+                        (
+                            "result",
+                            v.map_or("".to_string(), |v| Eval::display(&v)).into(),
+                        ),
+                        ("variablesReference", 0.into()),
+                    ])),
+                    Err(e) => Err(message(e)),
+                }
+            }
+            None => Err("NOT STOPPED".to_string()),
+        },
+        "setVariable" => match &mut stopped {
+            Some((eval, _)) => {
+                let name = args.get("name").as_str().unwrap_or_default();
+                let value = args.get("value").as_str().unwrap_or_default();
+                match Debugger::eval_line(eval, &format!("{} be {}", name, value))
+                    .and_then(|_| Debugger::eval_line(eval, name))
+                {
+                    Ok(v) => Ok(Json::object(vec![(
+                        "value",
+                        v.map_or("".to_string(), |v| Eval::display(&v)).into(),
+                    )])),
+                    Err(e) => Err(message(e)),
+                }
+            }
+            None => Err("NOT STOPPED".to_string()),
+        },
+        cmd @ ("continue" | "next" | "stepIn" | "stepOut") => {
+            session.borrow_mut().mode = match cmd {
+                "continue" => Mode::Continue,
+                "next" => Mode::Next(depth),
+                "stepIn" => Mode::Step,
+                _ => Mode::Out(depth),
+            };
+            action = Action::Resume;
+            Ok(Json::object(vec![("allThreadsContinued", true.into())]))
+        }
+        // Can't interrupt a running program from the same thread
+        "pause" => Ok(Json::object(vec![])),
+        "disconnect" | "terminate" => {
+            action = Action::Disconnect;
+            Ok(Json::object(vec![]))
+        }
+        cmd => Err(format!("{} NOT SUPPORTED", cmd)),
+    };
+
+    session.borrow_mut().respond(request, body)?;
+    Ok(action)
+}
+
+fn message(e: Errors) -> String {
+    match e {
+        Errors::RuntimeError(msg, ..) | Errors::SyntaxError(msg, ..) => msg,
+        e => e.to_string(),
+    }
+}
+
+fn stack_frames(eval: &Eval, line: usize, session: &Shared) -> Vec<Json> {
+    let path = session.borrow().program.clone().unwrap_or_default();
+    let frames = eval.call_stack().iter().filter(|frame| !frame.done());
+    frames
+        .rev()
+        .enumerate()
+        .map(|(i, frame)| {
+            // Frames below the top were left at the line after their 'run'
+            let at = if i == 0 { line } else { frame.from - 1 };
+            Json::object(vec![
+                ("id", (i + 1).into()),
+                ("name", format!("lines {}-{}", frame.start, frame.to).into()),
+                ("line", at.into()),
+                ("column", 1.into()),
+                ("source", Json::object(vec![("path", path.as_str().into())])),
+            ])
+        })
+        .collect()
+}
+
+// Sends 'write's to the editor's console, and 'read's come from the
+// launch's input
+struct Output<'a> {
+    session: Shared<'a>,
+    input: Option<Buffer>,
+}
+
+impl<'a> Io for Output<'a> {
+    fn write_line(&mut self, text: &str) -> Result<(), Errors> {
+        self.session.borrow_mut().event(
+            "output",
+            Json::object(vec![
+                ("category", "stdout".into()),
+                ("output", format!("{}\n", text).into()),
+            ]),
+        )
+    }
+
+    // Without an input there's nothing to answer with
+    fn read_line(&mut self) -> Result<String, Errors> {
+        match &mut self.input {
+            Some(input) => input.read_line(),
+            None => Err(Errors::ArgumentError(
+                "CAN'T READ WHILE DEBUGGING. GIVE IT input IN launch.json".to_string(),
+            )),
+        }
+    }
+}
+
+struct Adapter<'a> {
+    session: Shared<'a>,
+    entry: bool,
+}
+
+impl<'a> Hook for Adapter<'a> {
+    fn line(&mut self, eval: &mut Eval, line: usize) -> Result<(), Errors> {
+        let reason = {
+            let s = self.session.borrow();
+            if self.entry {
+                Some("entry")
+            } else if s.breakpoints.contains(&line) {
+                Some("breakpoint")
+            } else if s.mode.stops(Debugger::depth(eval)) {
+                Some("step")
+            } else {
+                None
+            }
+        };
+        self.entry = false;
+        let Some(reason) = reason else {
+            return Ok(());
+        };
+
+        self.session.borrow_mut().event(
+            "stopped",
+            Json::object(vec![
+                ("reason", reason.into()),
+                ("threadId", 1.into()),
+                ("allThreadsStopped", true.into()),
+            ]),
+        )?;
+
+        loop {
+            let request = self.session.borrow_mut().recv()?;
+            let action = match &request {
+                Some(request) => handle(&self.session, request, Some((eval, line)))?,
+                None => Action::Disconnect,
+            };
+            match action {
+                Action::Stay => (),
+                Action::Resume => return Ok(()),
+                Action::Disconnect => {
+                    self.session.borrow_mut().disconnected = true;
+                    return Err(Errors::EndProg);
+                }
+            }
+        }
+    }
+}
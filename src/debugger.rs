@@ -0,0 +1,219 @@
+use std::{
+    collections::BTreeSet,
+    io::{stdin, stdout, Write},
+};
+
+use crate::{
+    ast::Stmt,
+    errors::Errors,
+    eval::{Eval, Hook},
+    lexer::Reader,
+};
+
+const HELP: &str = "\
+s, step          run one line, going into 'run's
+n, next          run one line, stepping over 'run's
+o, out           run until the current 'run' is done
+c, continue      run until a breakpoint
+b, break LINE    stop before LINE runs
+d, delete LINE   remove that breakpoint
+bt, stack        show the 'run's we're in
+l, list          show the lines around here
+v, vars          show all variables
+p, print EXPR    show the value of EXPR
+set NAME be EXPR change a variable
+q, quit          stop the program";
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Mode {
+    Step,
+    // Stop once no deeper than this
+    Next(usize),
+    // Stop once shallower than this
+    Out(usize),
+    Continue,
+}
+
+impl Mode {
+    // Whether to stop at a line this deep in 'run's
+    pub(crate) fn stops(self, depth: usize) -> bool {
+        match self {
+            Mode::Step => true,
+            Mode::Next(next) => depth <= next,
+            Mode::Out(out) => depth < out,
+            Mode::Continue => false,
+        }
+    }
+}
+
+// Interactive debugger on stdin, stops on the first line
+pub struct Debugger {
+    breakpoints: BTreeSet<usize>,
+    mode: Mode,
+}
+
+impl Default for Debugger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debugger {
+    pub fn new() -> Self {
+        Self {
+            breakpoints: BTreeSet::new(),
+            mode: Mode::Step,
+        }
+    }
+
+    pub fn set_breakpoint(&mut self, line: usize) {
+        self.breakpoints.insert(line);
+    }
+
+    // Finished frames stay on the call stack until the 'run' below them is
+    // done, so only frames with lines left count
+    pub fn depth(eval: &Eval) -> usize {
+        eval.call_stack().iter().filter(|frame| !frame.done()).count()
+    }
+
+    fn should_stop(&self, eval: &Eval, line: usize) -> bool {
+        if self.breakpoints.contains(&line) {
+            return true;
+        }
+        self.mode.stops(Self::depth(eval))
+    }
+
+    fn prompt(&mut self, eval: &mut Eval, line: usize) -> Result<(), Errors> {
+        loop {
+            print!("(debug) ");
+            stdout().flush().map_err(|_| Errors::IOError)?;
+
+            let mut input = String::new();
+            if stdin().read_line(&mut input).map_err(|_| Errors::IOError)? == 0 {
+                // Nobody's there anymore
+                self.mode = Mode::Continue;
+                return Ok(());
+            }
+
+            let input = input.trim();
+            let (cmd, arg) = match input.split_once(' ') {
+                Some((cmd, arg)) => (cmd, arg.trim()),
+                None => (input, ""),
+            };
+
+            match cmd {
+                "s" | "step" => self.mode = Mode::Step,
+                "n" | "next" => self.mode = Mode::Next(Self::depth(eval)),
+                "o" | "out" => self.mode = Mode::Out(Self::depth(eval)),
+                "c" | "continue" => self.mode = Mode::Continue,
+                "b" | "break" | "d" | "delete" => {
+                    match arg.parse::<usize>() {
+                        Ok(n) if cmd.starts_with('b') => {
+                            self.breakpoints.insert(n);
+                        }
+                        Ok(n) => {
+                            self.breakpoints.remove(&n);
+                        }
+                        Err(_) => println!("NEED LINE NUMBER"),
+                    }
+                    let lines: Vec<_> = self.breakpoints.iter().map(usize::to_string).collect();
+                    println!("BREAKPOINTS: {}", lines.join(" "));
+                    continue;
+                }
+                "bt" | "stack" => {
+                    Self::print_stack(eval, line);
+                    continue;
+                }
+                "l" | "list" => {
+                    Self::print_lines(eval, line);
+                    continue;
+                }
+                "v" | "vars" => {
+                    let mut vars: Vec<_> = eval.vars().collect();
+                    vars.sort_by(|a, b| a.0.cmp(b.0));
+                    for (name, value) in vars {
+                        println!("{} be {}", name, Eval::display(value));
+                    }
+                    continue;
+                }
+                "p" | "print" => {
+                    match Self::eval_line(eval, arg) {
+                        Ok(Some(v)) => println!("{}", Eval::display(&v)),
+                        Ok(None) => println!("NOT AN EXPRESSION"),
+                        Err(e) => Self::print_error(e),
+                    }
+                    continue;
+                }
+                "set" => {
+                    match Self::eval_line(eval, arg) {
+                        Ok(_) => (),
+                        Err(e) => Self::print_error(e),
+                    }
+                    continue;
+                }
+                "q" | "quit" => return Err(Errors::EndProg),
+                "h" | "help" => {
+                    println!("{}", HELP);
+                    continue;
+                }
+                "" => continue,
+                _ => {
+                    println!("WHAT IS {}? TRY help", cmd);
+                    continue;
+                }
+            }
+            return Ok(());
+        }
+    }
+
+    // Run one expression or assignment at the stop point
+    pub(crate) fn eval_line(eval: &mut Eval, line: &str) -> Result<Option<crate::Value>, Errors> {
+        let mut reader = Reader::from(line);
+        let prog = crate::parse(&mut reader)?;
+        match prog.first() {
+            Some(Stmt::Expr(expr)) => Ok(Some(eval.eval_expr(expr)?)),
+            Some(stmt @ Stmt::Asgn(..)) => {
+                eval.run_stmt(stmt)?;
+                Ok(None)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn print_error(e: Errors) {
+        match e {
+            // The context would be a line of the program, not what was typed
+            Errors::RuntimeError(msg, ..) => println!("{}", msg),
+            e => println!("{}", e),
+        }
+    }
+
+    fn print_stack(eval: &Eval, line: usize) {
+        let frames = eval.call_stack().iter().filter(|frame| !frame.done());
+        for (i, frame) in frames.rev().enumerate() {
+            // Frames below the top were left at the line after their 'run'
+            let at = if i == 0 { line } else { frame.from - 1 };
+            println!("#{} line {} in lines {}-{}", i, at, frame.start, frame.to);
+        }
+    }
+
+    fn print_lines(eval: &Eval, line: usize) {
+        for n in line.saturating_sub(3).max(1)..=line + 3 {
+            if n > eval.line_count() {
+                break;
+            }
+            let mark = if n == line { "->" } else { "  " };
+            println!("{} {:>4} | {}", mark, n, eval.line_text(n));
+        }
+    }
+}
+
+impl Hook for Debugger {
+    fn line(&mut self, eval: &mut Eval, line: usize) -> Result<(), Errors> {
+        if !self.should_stop(eval, line) {
+            return Ok(());
+        }
+        println!("STOPPED AT LINE {}: {}", line, eval.line_text(line).trim());
+        self.prompt(eval, line)
+    }
+}
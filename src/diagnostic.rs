@@ -0,0 +1,195 @@
+use crate::{
+    errors::{Errors, Kind},
+    json::Json,
+    lexer::{Pos, Span},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+}
+
+// An error as data, for tools instead of people
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+    // 0 if it's not about a line
+    pub line: usize,
+    // Character columns from 1, end not included
+    pub span: (usize, usize),
+    pub source_line: String,
+    pub notes: Vec<String>,
+}
+
+// What went wrong -> code, note
+fn describe(kind: Kind) -> (&'static str, &'static str) {
+    use Kind::*;
+    match kind {
+        // Lexer
+        WeirdSymbol => (
+            "E101",
+            "only letters, digits and \" are allowed, operators are words like 'plus'",
+        ),
+        MissingQuote => ("E102", "text has to end with \" on the same line"),
+        NumberTooLarge => (
+            "E103",
+            "whole numbers go up to 170141183460469231731687303715884105727, or more with the bigint feature",
+        ),
+        CarriageWithoutReturn => ("E104", "lines end with \\n or \\r\\n"),
+        MissingQuotes => ("E105", "text started with \"\"\" has to end with \"\"\""),
+        EscapeNeverEnds => ("E106", "escapes are a word between backslashes, like \\quote\\"),
+        WeirdEscape => (
+            "E107",
+            "the escapes are \\quote\\ \\newline\\ \\tab\\ \\backslash\\ and \\unicode 1F600\\",
+        ),
+        InvalidUnicode => ("E108", "\\unicode CODE\\ needs a code point in hex, like 1F600"),
+        // Parser
+        WhyThisHere => ("E111", "each line is one statement, optionally followed by 'if'"),
+        NeedValue => ("E112", ""),
+        NeedClose => ("E113", "every 'open' needs a 'close'"),
+        WhereIdentifier => ("E114", "only names can 'be' something"),
+        InvalidDecimal => ("E115", "decimals are written like 3 dot 14"),
+        ExpectedLiteral => ("E116", ""),
+        ExpectedName => ("E117", ""),
+        NeedLabelName => ("E118", "write 'label NAME' at the start of a line"),
+        LabelUsedTwice => ("E119", "each label can only name one line"),
+        NeedOf => ("E120", "like 'length of X' or 'letter N of X'"),
+        NeedTo => ("E121", "like 'part A to B of X'"),
+        NeedIn => ("E122", "like 'find X in Y'"),
+        KeywordNow => (
+            "E123",
+            "newer versions of nooblang use this word, give the name another one",
+        ),
+        // Evaluator
+        VariableNotFound => ("E201", "give it a value with 'be' before using it"),
+        InvalidLineRange => (
+            "E202",
+            "'run A to B' needs whole numbers with 1 <= A <= B <= the last line",
+        ),
+        InvalidLineNumber => ("E203", "'run A' needs a whole number between 1 and the last line"),
+        CantCompare => ("E204", "only numbers compare with numbers and text with text"),
+        CantAdd => ("E205", ""),
+        CantSubtract => ("E206", ""),
+        CantMultiply => ("E207", ""),
+        CantDivide => ("E208", ""),
+        CantRaise => ("E209", ""),
+        CantModolo => ("E210", ""),
+        CantNegate => ("E211", ""),
+        NothingToRepeat => (
+            "E212",
+            "a 'repeat' on its own needs a 'run' to have happened before it",
+        ),
+        CantRepeat => ("E213", "'repeat' needs a whole number of times, 0 or more"),
+        CantRound => ("E214", ""),
+        CantAbs => ("E215", ""),
+        CantSqrt => ("E216", "'sqrt' needs a number that's 0 or more"),
+        CantMeasure => ("E217", "'length of' needs text"),
+        CantCut => ("E218", "'letter' and 'part' need text after 'of'"),
+        InvalidLetterNumber => (
+            "E219",
+            "'letter N of X' needs a whole number between 1 and the length of X",
+        ),
+        InvalidLetterRange => (
+            "E220",
+            "'part A to B of X' needs whole numbers with 1 <= A <= B + 1 and B <= the length of X",
+        ),
+        CantSearch => ("E221", "'find X in Y' needs text on both sides"),
+        CantChangeCase => ("E222", ""),
+        CantGetCode => ("E223", "'code of' needs text with at least one letter"),
+        InvalidCharacterCode => (
+            "E224",
+            "'character N' needs a whole number that's a Unicode code point",
+        ),
+        OutOfSteps => (
+            "E225",
+            "the program ran more lines than --max-steps allows, it might loop forever",
+        ),
+        OutOfTime => (
+            "E226",
+            "the program ran longer than --max-time allows, it might loop forever",
+        ),
+        TooDeep => (
+            "E227",
+            "more unfinished 'run's than --max-depth allows, a 'run' that goes back up never finishes",
+        ),
+        ModoloByZero => ("E228", "'A mod B' needs a B that isn't 0"),
+        DivideByZero => ("E229", "whole numbers and fractions can't be divided by 0"),
+    }
+}
+
+impl Diagnostic {
+    // One for each error in it
+    pub fn all(e: &Errors) -> Vec<Self> {
+        match e {
+            Errors::Multiple(errors) => errors.iter().flat_map(Self::all).collect(),
+            e => vec![Self::from_error(e)],
+        }
+    }
+
+    pub fn from_error(e: &Errors) -> Self {
+        let nowhere = Span(Pos(0, 0), Pos(0, 0));
+        let (message, span, source_line, kind) = match e {
+            Errors::SyntaxError(msg, span, context, kind)
+            | Errors::RuntimeError(msg, span, context, kind) => {
+                (msg.clone(), *span, context.clone(), Some(*kind))
+            }
+            e => (e.to_string(), nowhere, String::new(), None),
+        };
+
+        let (code, notes) = match (kind.map(describe), e) {
+            (Some((code, "")), _) => (code, Vec::new()),
+            (Some((code, note)), _) => (code, vec![note.to_string()]),
+            (None, Errors::ArgumentError(_)) => ("E001", Vec::new()),
+            (None, _) => ("E000", Vec::new()),
+        };
+
+        Self {
+            severity: Severity::Error,
+            code,
+            message,
+            line: span.line(),
+            span: columns(&source_line, span),
+            source_line,
+            notes,
+        }
+    }
+
+    pub fn to_json(&self) -> Json {
+        let severity = match self.severity {
+            Severity::Error => "error",
+        };
+        Json::object(vec![
+            ("severity", severity.into()),
+            ("code", self.code.into()),
+            ("message", self.message.as_str().into()),
+            ("line", self.line.into()),
+            ("column_start", self.span.0.into()),
+            ("column_end", self.span.1.into()),
+            ("source_line", self.source_line.as_str().into()),
+            (
+                "notes",
+                self.notes
+                    .iter()
+                    .map(|note| note.as_str().into())
+                    .collect::<Vec<Json>>()
+                    .into(),
+            ),
+        ])
+    }
+}
+
+// On the first line only, and at least one column
+fn columns(source_line: &str, span: Span) -> (usize, usize) {
+    let Span(Pos(line, start), Pos(end_line, end)) = span;
+    if start == 0 {
+        return (0, 0);
+    }
+    let end = if end_line > line {
+        source_line.chars().count() + 1
+    } else {
+        end
+    };
+    (start, end.max(start + 1))
+}
@@ -1,59 +1,198 @@
 use core::fmt;
 use std::{error::Error, fmt::Display};
 
-use crate::lexer::Pos;
+use crate::lexer::{Pos, Span};
 
+/// Everything that can stop a program, or nooblang itself.
 #[derive(Debug)]
 pub enum Errors {
-    ArgumentError,
-    SyntaxError(String, Pos, String),
+    /// Bad command line arguments, or a file that isn't what it should be.
+    ArgumentError(String),
+    /// A typo found before the program runs: the message, where it is, the
+    /// text of its line and what went wrong.
+    SyntaxError(String, Span, String, Kind),
+    /// Reading or writing a file or the terminal failed.
     IOError,
-    RuntimeError(String, Pos, String),
-    Interrupt(usize, usize),
+    /// The program went wrong while running, with the same fields as
+    /// [`Errors::SyntaxError`].
+    RuntimeError(String, Span, String, Kind),
+    /// Not really an error: a `run` from, to, how many more times. The
+    /// evaluator catches it to jump.
+    Interrupt(usize, usize, u128),
+    /// Not really an error either: `end`, or a debugger quitting.
     EndProg,
+    /// More than one syntax error, in line order.
+    Multiple(Vec<Errors>),
+}
+
+// What went wrong, so tools don't have to read the message to know
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    // Lexer
+    WeirdSymbol,
+    MissingQuote,
+    NumberTooLarge,
+    CarriageWithoutReturn,
+    MissingQuotes,
+    EscapeNeverEnds,
+    WeirdEscape,
+    InvalidUnicode,
+    // Parser
+    WhyThisHere,
+    NeedValue,
+    NeedClose,
+    WhereIdentifier,
+    InvalidDecimal,
+    ExpectedLiteral,
+    ExpectedName,
+    NeedLabelName,
+    LabelUsedTwice,
+    NeedOf,
+    NeedTo,
+    NeedIn,
+    KeywordNow,
+    // Evaluator
+    VariableNotFound,
+    InvalidLineRange,
+    InvalidLineNumber,
+    CantCompare,
+    CantAdd,
+    CantSubtract,
+    CantMultiply,
+    CantDivide,
+    CantRaise,
+    CantModolo,
+    CantNegate,
+    NothingToRepeat,
+    CantRepeat,
+    CantRound,
+    CantAbs,
+    CantSqrt,
+    CantMeasure,
+    CantCut,
+    InvalidLetterNumber,
+    InvalidLetterRange,
+    CantSearch,
+    CantChangeCase,
+    CantGetCode,
+    InvalidCharacterCode,
+    OutOfSteps,
+    OutOfTime,
+    TooDeep,
+    ModoloByZero,
+    DivideByZero,
+}
+
+impl Errors {
+    /// None, the one error, or all of them together.
+    pub fn combine(mut errors: Vec<Errors>) -> Option<Errors> {
+        errors.sort_by_key(|e| match e {
+            Self::SyntaxError(_, Span(Pos(line, col), _), ..) => (*line, *col),
+            _ => (0, 0),
+        });
+        match errors.len() {
+            0 => None,
+            1 => errors.pop(),
+            _ => Some(Self::Multiple(errors)),
+        }
+    }
 }
 
 impl Error for Errors {}
 impl Display for Errors {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Self::ArgumentError => {
-                writeln!(f, "NEED SOURCE FILE AS ARGUMENT")
+            Self::ArgumentError(e) => {
+                writeln!(f, "{}", e)
             },
-            Self::SyntaxError(e, Pos(line, col), context) => {
+            Self::SyntaxError(e, span, context, _) => {
                 writeln!(f, "--------- TYPO ---------")?;
                 writeln!(f, "{}", e)?;
-                writeln!(f, "IN LINE: {}", line)?;
+                writeln!(f, "IN LINE: {}", span.line())?;
                 writeln!(f)?;
 
                 // Cool stuff
                 write!(f, "--> ")?;
                 write!(f, "{}", context)?;
 
-                write!(f, "\n{}^-- SEE", " ".repeat(col + 3))
+                underline(f, context, span, "SEE")
             }
 
             Self::IOError => {
                 write!(f, "Failed to read file :^) sorry")
             }
-            Self::RuntimeError(e, Pos(line, col), context) => {
+            Self::RuntimeError(e, span, context, _) => {
                 writeln!(f, "--------- ERROR ---------")?;
                 writeln!(f, "{}", e)?;
-                writeln!(f, "IN LINE: {}", line)?;
+                writeln!(f, "IN LINE: {}", span.line())?;
                 writeln!(f)?;
 
                 // Cool stuff
                 write!(f, "--> ")?;
                 write!(f, "{}", context)?;
 
-                write!(f, "\n{}^-- LOOK", " ".repeat(col + 3))
+                underline(f, context, span, "LOOK")
             },
-            Self::Interrupt(from, to) => {
+            Self::Interrupt(from, to, _) => {
                 writeln!(f, "Control flow changed to lines: {}-{}", from, to)
             }
             Self::EndProg => {
                 write!(f, "Program ended")
             },
+            Self::Multiple(errors) => {
+                for e in errors {
+                    writeln!(f, "{}", e)?;
+                    writeln!(f)?;
+                }
+                write!(f, "{} TYPOS FOUND", errors.len())
+            }
         }
     }
 }
+
+// ^^^ under the whole span, to the end of the line if it goes past it.
+// Tabs before it are copied so it lines up however wide they show, and
+// wide letters get two spaces or marks.
+fn underline(f: &mut fmt::Formatter, context: &str, span: &Span, say: &str) -> fmt::Result {
+    let Span(Pos(line, start), Pos(end_line, end)) = *span;
+    let len = context.chars().count();
+    let end = if end_line > line { len + 1 } else { end };
+    // Past the end of the line is one column each
+    let mut chars = context.chars().map(Some).chain(std::iter::repeat(None));
+    let pad: String = chars
+        .by_ref()
+        .take(start.saturating_sub(1))
+        .map(|c| match c {
+            Some('\t') => "\t".to_string(),
+            c => " ".repeat(c.map_or(1, width)),
+        })
+        .collect();
+    let wide: usize = chars
+        .take(end.saturating_sub(start))
+        .map(|c| c.map_or(1, width))
+        .sum();
+    let marks = "^".repeat(wide.max(1));
+    write!(f, "\n    {}{}-- {}", pad, marks, say)
+}
+
+// Columns a character takes up in a terminal, near enough without all of
+// Unicode's tables
+fn width(c: char) -> usize {
+    match c as u32 {
+        // Accents that go on the letter before them
+        0x300..=0x36F | 0x200B..=0x200F => 0,
+        // Hangul, Chinese, Japanese, full width forms and emoji
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1F64F
+        | 0x1F900..=0x1F9FF
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
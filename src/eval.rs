@@ -1,161 +1,376 @@
-use std::{
-    collections::HashMap,
-    io::{stdout, Write},
-};
+use std::{collections::HashMap, time::Instant};
 
 use crate::{
     ast::*,
-    errors::Errors,
-    lexer::{Lexer, Pos, Reader},
+    errors::{Errors, Kind},
+    lexer::{Lexer, Reader, Span},
+    ratio::Ratio,
 };
 
+#[cfg(feature = "bigint")]
+use crate::bigint::Big;
+
 mod arith;
+#[cfg(feature = "bigint")]
+mod big;
 mod cmp;
 mod control;
 mod conv;
+mod hook;
 mod io;
+mod limits;
+mod math;
+mod ratio;
+mod text;
 mod var;
 
+pub use hook::Hook;
+pub use io::{Buffer, Callback, Io, Stdio};
+pub use limits::Limits;
+
 #[derive(Debug, PartialEq, Eq, Hash)]
 
 pub struct Name {
-    id: String,
+    pub id: String,
     // scope
 }
 
+/// What an expression gives, and what variables hold.
 #[derive(Debug, Clone)]
 pub enum Value {
+    /// A whole number.
     Int(i128),
+    /// A whole number that doesn't fit in [`Value::Int`].
+    #[cfg(feature = "bigint")]
+    Big(Big),
+    /// An exact number that isn't whole, like 1/3 or 0.1.
+    Ratio(Ratio),
+    /// A number too big to be exact.
     Float(f64),
+    /// Text, from a literal, `read` or `text`.
     Text(String),
+    /// `yes` or `no`.
     Choice(bool),
+    /// What statements that aren't expressions give.
     Nothing,
 }
 
+// A 'run' in progress
+#[derive(Debug, Clone, Copy)]
+pub struct Frame {
+    // Next line, last line
+    pub from: usize,
+    pub to: usize,
+    // First line, to go again from
+    pub start: usize,
+    // Times to go again after this one, for 'repeat'
+    pub left: u128,
+}
+
+impl Frame {
+    pub fn new(from: usize, to: usize, times: u128) -> Self {
+        Self {
+            from,
+            to,
+            start: from,
+            left: times - 1,
+        }
+    }
+
+    // Only waiting for the 'run's above it, nothing to do after
+    pub fn done(&self) -> bool {
+        self.from > self.to && self.left == 0
+    }
+}
+
+/// Runs a parsed program one statement at a time, straight from the tree.
+///
+/// `write` and `read` go to the terminal unless [`Eval::set_io`] says
+/// otherwise, and there are no limits unless [`Eval::set_limits`] sets them.
 pub struct Eval<'a> {
     input: &'a Vec<Stmt>,
     reader: &'a Reader,
     context: HashMap<Name, Value>,
-    // from, to
-    call_stack: Vec<(usize, usize)>,
+    call_stack: Vec<Frame>,
+    // Lines of the last 'run', for a plain 'repeat'
+    last_run: Option<(usize, usize)>,
+    hook: Option<Box<dyn Hook + 'a>>,
+    // For 'write' and 'read', stdio if not set
+    io: Box<dyn Io + 'a>,
+    limits: Limits,
+    // Used up so far
+    steps: u64,
+    started: Instant,
 }
 
 impl<'a> Eval<'a> {
+    /// The program from [`crate::parse`], and the reader it came from to show
+    /// lines in errors.
     pub fn new(input: &'a Vec<Stmt>, reader: &'a Reader) -> Self {
         Self {
             input,
             reader,
             context: HashMap::new(),
             call_stack: Vec::new(),
+            last_run: None,
+            hook: None,
+            io: Box::new(Stdio),
+            limits: Limits::default(),
+            steps: 0,
+            started: Instant::now(),
         }
     }
 
+    /// Runs the whole program, giving the value of the last statement run.
     pub fn run_prog(&mut self) -> Result<Value, Errors> {
+        self.run_lines(1, self.input.len())
+    }
+
+    /// Like `run from to`, but from outside the program.
+    pub fn run_lines(&mut self, from: usize, to: usize) -> Result<Value, Errors> {
         let mut ret = Value::Nothing;
-        self.call_stack.push((1, self.input.len()));
-        'outer: loop {
-            let Some((from, to)) = self.call_stack.last() else {break};
-            for i in *from - 1..*to {
+        self.call_stack = vec![Frame::new(from, to, 1)];
+        self.start_limits();
+        'outer: while let Some(&Frame { from, to, .. }) = self.call_stack.last() {
+            for i in from - 1..to {
                 let stmt = &self.input[i];
-                let v = self.run_stmt(stmt);
+                let v = self
+                    .spend(i + 1)
+                    .and_then(|_| self.before_line(i + 1))
+                    .and_then(|_| self.run_stmt(stmt));
                 match v {
                     // 'end'
                     Err(Errors::EndProg) => break 'outer,
                     // 'run'
-                    Err(Errors::Interrupt(from, to)) => {
+                    Err(Errors::Interrupt(from, to, times)) => {
                         // Save current line number
                         let current = self
                             .call_stack
                             .last_mut()
                             .expect("NONEXISTENT CURRENT STACK FRAME");
-                        current.0 = i + 2;
+                        current.from = i + 2;
 
-                        self.call_stack.push((from, to));
+                        self.last_run = Some((from, to));
+                        if times > 0 {
+                            // A 'run' on its last line has nothing to come
+                            // back to, so loops like that don't pile up
+                            if current.done() {
+                                self.call_stack.pop();
+                            }
+                            self.hook_run(i + 1, from, to);
+                            self.call_stack.push(Frame::new(from, to, times));
+                            self.check_depth(i + 1)?;
+                        }
                         continue 'outer;
                     }
                     _ => ret = v?,
                 }
             }
-            self.call_stack.pop();
+
+            let current = self
+                .call_stack
+                .last_mut()
+                .expect("NONEXISTENT CURRENT STACK FRAME");
+            if current.left > 0 {
+                // 'repeat'
+                current.left -= 1;
+                current.from = current.start;
+            } else {
+                self.call_stack.pop();
+            }
         }
         Ok(ret)
     }
 
+    /// One statement. A `run` or `end` comes back as [`Errors::Interrupt`]
+    /// or [`Errors::EndProg`] for the caller to act on.
     pub fn run_stmt(&mut self, stmt: &Stmt) -> Result<Value, Errors> {
         match stmt {
             Stmt::Expr(expr) => return self.eval_expr(expr),
-            Stmt::Write(expr, line) => self.write(expr.as_ref(), *line)?,
-            Stmt::RunFrom(from, to, line) => self.run_from(from.as_ref(), to.as_ref(), *line)?,
-            Stmt::RunAt(expr, line) => self.run_at(expr.as_ref(), *line)?,
-            Stmt::Switch(stmt, cond, line) => self.switch(stmt.as_ref(), cond.as_ref(), *line)?,
-            Stmt::Asgn(id, expr, line) => self.asgn(id.as_ref(), expr.as_ref(), *line)?,
+            Stmt::Write(expr, span) => self.write(expr.as_ref(), *span)?,
+            Stmt::RunFrom(from, to, span) => self.run_from(from.as_ref(), to.as_ref(), *span)?,
+            Stmt::RunAt(expr, span) => self.run_at(expr.as_ref(), *span)?,
+            Stmt::Repeat(run, times, span) => self.repeat(run.as_deref(), times, *span)?,
+            Stmt::Switch(stmt, cond, otherwise, span) => {
+                self.switch(stmt.as_ref(), cond.as_ref(), otherwise.as_deref(), *span)?
+            }
+            Stmt::Asgn(id, expr, span) => self.asgn(id.as_ref(), expr.as_ref(), *span)?,
             Stmt::Blank => (),
             Stmt::End => return Err(Errors::EndProg),
         }
         Ok(Value::Nothing)
     }
 
+    /// The value of an expression.
     pub fn eval_expr(&mut self, expr: &Expr) -> Result<Value, Errors> {
         match expr {
             Expr::Binary(op, left, right, location) => {
                 let left = self.eval_expr(left.as_ref())?;
                 let right = self.eval_expr(right.as_ref())?;
-                match op {
-                    // arith.rs
-                    Operator::Plus => self.plus(&left, &right, *location),
-                    Operator::Minus => self.minus(&left, &right, *location),
-                    Operator::Times => self.times(&left, &right, *location),
-                    Operator::Over => self.over(&left, &right, *location),
-                    Operator::Mod => self.modolo(&left, &right, *location),
-                    Operator::ToThe => self.pow(&left, &right, *location),
-
-                    // cmp.rs
-                    Operator::Or => Ok(self.or(&left, &right)),
-                    Operator::And => Ok(self.and(&left, &right)),
-                    Operator::Is => Ok(self.is(&left, &right)),
-                    Operator::Isnt => Ok(self.isnt(&left, &right)),
-                    Operator::Below => self.below(&left, &right, *location),
-                    Operator::Above => self.above(&left, &right, *location),
-                    Operator::AtMost => self.atmost(&left, &right, *location),
-                    Operator::AtLeast => self.atleast(&left, &right, *location),
-
-                    _ => Ok(Value::Nothing),
-                }
+                self.binary(op, &left, &right, *location)
+            }
+            Expr::Ternary(op, first, second, third, location) => {
+                let first = self.eval_expr(first.as_ref())?;
+                let second = self.eval_expr(second.as_ref())?;
+                let third = self.eval_expr(third.as_ref())?;
+                self.ternary(op, &first, &second, &third, *location)
             }
             Expr::Unary(op, operand, location) => {
                 let operand = self.eval_expr(operand.as_ref())?;
-                match op {
-                    // arith.rs
-                    Operator::Neg => self.neg(&operand, *location),
+                self.unary(op, &operand, *location)
+            }
+            Expr::Nullary(op, location) => self.nullary(op, *location),
+            Expr::Literal(literal, _) => Ok(Self::literal(literal)),
+            Expr::Id(id, location) => self.id(&Name { id: id.to_owned() }, *location),
+        }
+    }
 
-                    // cmp.rs
-                    Operator::Not => Ok(self.not(&operand)),
+    // Operators on already evaluated operands, shared with the vm
+    pub fn binary(
+        &self,
+        op: &Operator,
+        left: &Value,
+        right: &Value,
+        location: Span,
+    ) -> Result<Value, Errors> {
+        match op {
+            // arith.rs
+            Operator::Plus => self.plus(left, right, location),
+            Operator::Minus => self.minus(left, right, location),
+            Operator::Times => self.times(left, right, location),
+            Operator::Over => self.over(left, right, location),
+            Operator::Mod => self.modolo(left, right, location),
+            Operator::ToThe => self.pow(left, right, location),
 
-                    // conv.rs
-                    Operator::Num => Ok(self.num(&operand, *location)),
-                    Operator::Text => Ok(self.text(&operand)),
-                    Operator::Choice => Ok(self.choice(&operand)),
+            // math.rs
+            Operator::Div => self.div(left, right, location),
+            Operator::Min => self.min(left, right, location),
+            Operator::Max => self.max(left, right, location),
 
-                    _ => Ok(Value::Nothing),
-                }
-            }
-            Expr::Nullary(op, location) => match op {
-                Operator::Read => self.read(*location),
-                _ => Ok(Value::Nothing),
-            },
-            Expr::Literal(literal, _) => Ok(match literal {
-                Literal::Integer(n) => Value::Int(*n as i128),
-                Literal::Decimal(n) => Value::Float(*n),
-                Literal::Text(s) => Value::Text(s.to_owned()),
-                Literal::Choice(b) => Value::Choice(*b),
-            }),
-            Expr::Id(id, location) => self.id(&Name { id: id.to_owned() }, *location),
+            // text.rs
+            Operator::Letter => self.letter(left, right, location),
+            Operator::Find => self.find(left, right, location),
+
+            // cmp.rs
+            Operator::Or => Ok(self.or(left, right)),
+            Operator::And => Ok(self.and(left, right)),
+            Operator::Is => Ok(self.is(left, right)),
+            Operator::Isnt => Ok(self.isnt(left, right)),
+            Operator::Below => self.below(left, right, location),
+            Operator::Above => self.above(left, right, location),
+            Operator::AtMost => self.atmost(left, right, location),
+            Operator::AtLeast => self.atleast(left, right, location),
+
+            _ => Ok(Value::Nothing),
         }
     }
 
+    pub fn unary(&self, op: &Operator, operand: &Value, location: Span) -> Result<Value, Errors> {
+        match op {
+            // arith.rs
+            Operator::Neg => self.neg(operand, location),
+
+            // math.rs
+            Operator::Floor => self.floor(operand, location),
+            Operator::Ceil => self.ceil(operand, location),
+            Operator::Round => self.round(operand, location),
+            Operator::Abs => self.abs(operand, location),
+            Operator::Sqrt => self.sqrt(operand, location),
+
+            // text.rs
+            Operator::Length => self.length(operand, location),
+            Operator::Upper => self.upper(operand, location),
+            Operator::Lower => self.lower(operand, location),
+            Operator::Code => self.code(operand, location),
+            Operator::Character => self.character(operand, location),
+
+            // cmp.rs
+            Operator::Not => Ok(self.not(operand)),
+
+            // conv.rs
+            Operator::Num => Ok(self.num(operand, location)),
+            Operator::Text => Ok(self.text(operand)),
+            Operator::Choice => Ok(self.choice(operand)),
+
+            _ => Ok(Value::Nothing),
+        }
+    }
+
+    pub fn ternary(
+        &self,
+        op: &Operator,
+        first: &Value,
+        second: &Value,
+        third: &Value,
+        location: Span,
+    ) -> Result<Value, Errors> {
+        match op {
+            // text.rs
+            Operator::Part => self.part(first, second, third, location),
+            _ => Ok(Value::Nothing),
+        }
+    }
+
+    pub fn nullary(&mut self, op: &Operator, location: Span) -> Result<Value, Errors> {
+        match op {
+            Operator::Read => self.read(location),
+            _ => Ok(Value::Nothing),
+        }
+    }
+
+    pub fn literal(literal: &Literal) -> Value {
+        match literal {
+            Literal::Integer(n) => Value::Int(*n as i128),
+            #[cfg(feature = "bigint")]
+            Literal::Big(n) => Value::Big(n.clone()),
+            Literal::Decimal(n) => Value::from(*n),
+            Literal::Float(n) => Value::Float(*n),
+            Literal::Text(s) => Value::Text(s.to_owned()),
+            Literal::Choice(b) => Value::Choice(*b),
+            Literal::Label(_, line) => Value::Int(*line as i128),
+        }
+    }
+
+    /// Keeps variables around between programs, e.g. lines in the REPL.
+    pub fn with_context(mut self, context: HashMap<Name, Value>) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// The variables, to give to the next [`Eval::with_context`].
+    pub fn into_context(self) -> HashMap<Name, Value> {
+        self.context
+    }
+
+    /// The `run`s in progress, the program itself first.
+    pub fn call_stack(&self) -> &[Frame] {
+        &self.call_stack
+    }
+
+    /// How many lines the program has.
+    pub fn line_count(&self) -> usize {
+        self.input.len()
+    }
+
+    /// The source of a line, as it's written.
+    pub fn line_text(&self, line: usize) -> String {
+        Lexer::to_string_lossy(self.reader.get_line(line))
+    }
+
+    /// Every variable and its value, in no order.
+    pub fn vars(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.context
+            .iter()
+            .map(|(name, value)| (name.id.as_str(), value))
+    }
+
+    /// A value as the REPL and debugger show it, text in quotes.
     pub fn display(value: &Value) -> String {
         match value {
             Value::Int(n) => n.to_string(),
+            #[cfg(feature = "bigint")]
+            Value::Big(n) => n.to_string(),
+            Value::Ratio(n) => n.to_string(),
             Value::Float(n) => n.to_string(),
             Value::Text(s) => format!("\"{}\"", s),
             Value::Choice(b) => (if *b { "yes" } else { "no" }).to_string(),
@@ -163,12 +378,12 @@ impl<'a> Eval<'a> {
         }
     }
 
-    fn runtime_error(&self, msg: String, location: Pos) -> Errors {
-        let Pos(line, col) = location;
+    pub(crate) fn runtime_error(&self, kind: Kind, msg: String, location: Span) -> Errors {
         Errors::RuntimeError(
             msg,
-            Pos(line, col),
-            Lexer::to_string_lossy(self.reader.get_line(line)),
+            location,
+            Lexer::to_string_lossy(self.reader.get_line(location.line())),
+            kind,
         )
     }
 }
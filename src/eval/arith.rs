@@ -1,95 +1,251 @@
 use super::*;
+use crate::ratio::Ratio;
 use Value::*;
 
 impl<'a> Eval<'a> {
-    pub fn plus(&self, a: &Value, b: &Value, location: Pos) -> Result<Value, Errors> {
+    pub fn plus(&self, a: &Value, b: &Value, location: Span) -> Result<Value, Errors> {
+        #[cfg(feature = "bigint")]
+        if let Some(v) = Self::big_arith(&Operator::Plus, a, b) {
+            return Ok(v);
+        }
+        if let Some(v) = Self::ratio_arith(&Operator::Plus, a, b) {
+            return Ok(v);
+        }
         Ok(match (a, b) {
             (Int(a), Int(b)) => match a.checked_add(*b) {
                 Some(v) => Int(v),
-                None => Float(*a as f64 + *b as f64),
+                None => Self::wide(&Operator::Plus, *a, *b),
             },
             (Int(a), Float(b)) => Float(*a as f64 + b),
             (Float(a), Int(b)) => Float(*a + *b as f64),
             (Float(a), Float(b)) => Float(a + b),
-            (Text(a), Text(b)) => Text(a.to_owned() + &b),
-            _ => return Err(self.runtime_error("CAN'T ADD DIS".to_string(), location)),
+            (Text(a), Text(b)) => Text(a.to_owned() + b),
+            _ => {
+                return Err(self.runtime_error(
+                    Kind::CantAdd,
+                    "CAN'T ADD DIS".to_string(),
+                    location,
+                ))
+            }
         })
     }
 
-    pub fn minus(&self, a: &Value, b: &Value, location: Pos) -> Result<Value, Errors> {
+    pub fn minus(&self, a: &Value, b: &Value, location: Span) -> Result<Value, Errors> {
+        #[cfg(feature = "bigint")]
+        if let Some(v) = Self::big_arith(&Operator::Minus, a, b) {
+            return Ok(v);
+        }
+        if let Some(v) = Self::ratio_arith(&Operator::Minus, a, b) {
+            return Ok(v);
+        }
         Ok(match (a, b) {
             (Int(a), Int(b)) => match a.checked_sub(*b) {
                 Some(v) => Int(v),
-                None => Float(*a as f64 - *b as f64),
+                None => Self::wide(&Operator::Minus, *a, *b),
             },
             (Int(a), Float(b)) => Float(*a as f64 - b),
             (Float(a), Int(b)) => Float(a - *b as f64),
             (Float(a), Float(b)) => Float(a - b),
-            _ => return Err(self.runtime_error("CAN'T SUBTRACT DIS".to_string(), location)),
+            _ => {
+                return Err(self.runtime_error(
+                    Kind::CantSubtract,
+                    "CAN'T SUBTRACT DIS".to_string(),
+                    location,
+                ))
+            }
         })
     }
 
-    pub fn times(&self, a: &Value, b: &Value, location: Pos) -> Result<Value, Errors> {
+    pub fn times(&self, a: &Value, b: &Value, location: Span) -> Result<Value, Errors> {
+        #[cfg(feature = "bigint")]
+        if let Some(v) = Self::big_arith(&Operator::Times, a, b) {
+            return Ok(v);
+        }
+        if let Some(v) = Self::ratio_arith(&Operator::Times, a, b) {
+            return Ok(v);
+        }
         Ok(match (a, b) {
             (Int(a), Int(b)) => match a.checked_mul(*b) {
                 Some(v) => Int(v),
-                None => Float(*a as f64 * *b as f64),
+                None => Self::wide(&Operator::Times, *a, *b),
             },
             (Int(a), Float(b)) => Float(*a as f64 * b),
             (Float(a), Int(b)) => Float(a * *b as f64),
             (Float(a), Float(b)) => Float(a * b),
-            _ => return Err(self.runtime_error("CAN'T MULTIPLY DIS".to_string(), location)),
+            _ => {
+                return Err(self.runtime_error(
+                    Kind::CantMultiply,
+                    "CAN'T MULTIPLY DIS".to_string(),
+                    location,
+                ))
+            }
         })
     }
 
-    pub fn over(&self, a: &Value, b: &Value, location: Pos) -> Result<Value, Errors> {
+    pub fn over(&self, a: &Value, b: &Value, location: Span) -> Result<Value, Errors> {
+        if Self::by_zero(a, b) {
+            return Err(self.runtime_error(
+                Kind::DivideByZero,
+                "CAN'T DIVIDE BY ZERO".to_string(),
+                location,
+            ));
+        }
+        #[cfg(feature = "bigint")]
+        if let Some(v) = Self::big_arith(&Operator::Over, a, b) {
+            return Ok(v);
+        }
+        if let Some(v) = Self::ratio_arith(&Operator::Over, a, b) {
+            return Ok(v);
+        }
         Ok(match (a, b) {
-            (Int(a), Int(b)) => match a.checked_mul(*b) {
-                Some(v) => Int(v),
-                None => Float(*a as f64 / *b as f64),
+            // Exact, unless it's too big for a fraction
+            (Int(a), Int(b)) => match Ratio::new(*a, *b) {
+                Some(v) => Value::from(v),
+                None => Self::wide(&Operator::Over, *a, *b),
             },
             (Int(a), Float(b)) => Float(*a as f64 / b),
             (Float(a), Int(b)) => Float(a / *b as f64),
             (Float(a), Float(b)) => Float(a / b),
-            _ => return Err(self.runtime_error("CAN'T DIVIDE DIS".to_string(), location)),
+            _ => {
+                return Err(self.runtime_error(
+                    Kind::CantDivide,
+                    "CAN'T DIVIDE DIS".to_string(),
+                    location,
+                ))
+            }
         })
     }
 
-    pub fn pow(&self, a: &Value, b: &Value, location: Pos) -> Result<Value, Errors> {
+    pub fn pow(&self, a: &Value, b: &Value, location: Span) -> Result<Value, Errors> {
+        #[cfg(feature = "bigint")]
+        if let Some(v) = Self::big_arith(&Operator::ToThe, a, b) {
+            return Ok(v);
+        }
+        if let Some(v) = Self::ratio_arith(&Operator::ToThe, a, b) {
+            return Ok(v);
+        }
         Ok(match (a, b) {
             (Int(a), Int(b)) => {
                 if let Ok(b) = u32::try_from(*b) {
-                    match a.checked_pow(b) {
-                        Some(v) => return Ok(Int(v)),
-                        _ => (),
+                    if let Some(v) = a.checked_pow(b) {
+                        return Ok(Int(v));
                     }
                 }
-                Float((*a as f64).powf(*b as f64))
+                // Below 0 it's a fraction
+                if let Some(v) = Ratio::whole(*a).checked_pow(*b) {
+                    return Ok(Value::from(v));
+                }
+                Self::wide(&Operator::ToThe, *a, *b)
             }
             (Int(a), Float(b)) => Float((*a as f64).powf(*b)),
             (Float(a), Int(b)) => Float(a.powf(*b as f64)),
             (Float(a), Float(b)) => Float(a.powf(*b)),
             _ => {
-                return Err(self.runtime_error("CAN'T RAISE TO POWER OF DIS".to_string(), location))
+                return Err(self.runtime_error(
+                    Kind::CantRaise,
+                    "CAN'T RAISE TO POWER OF DIS".to_string(),
+                    location,
+                ))
             }
         })
     }
 
-    pub fn modolo(&self, a: &Value, b: &Value, location: Pos) -> Result<Value, Errors> {
+    pub fn modolo(&self, a: &Value, b: &Value, location: Span) -> Result<Value, Errors> {
+        // Nothing is left over from dividing by 0, whatever kind of number
+        if Self::float(b) == Some(0.0) && Self::float(a).is_some() {
+            return Err(self.runtime_error(
+                Kind::ModoloByZero,
+                "CAN'T MODOLO BY ZERO".to_string(),
+                location,
+            ));
+        }
+        #[cfg(feature = "bigint")]
+        if let Some(v) = Self::big_arith(&Operator::Mod, a, b) {
+            return Ok(v);
+        }
+        if let Some(v) = Self::ratio_arith(&Operator::Mod, a, b) {
+            return Ok(v);
+        }
         Ok(match (a, b) {
-            (Int(a), Int(b)) => Int(a % b),
+            (Int(a), Int(b)) => match a.checked_rem(*b) {
+                Some(v) => Int(v),
+                None => Self::wide(&Operator::Mod, *a, *b),
+            },
             (Int(a), Float(b)) => Float(*a as f64 % b),
             (Float(a), Int(b)) => Float(a % *b as f64),
             (Float(a), Float(b)) => Float(a % b),
-            _ => return Err(self.runtime_error("CAN'T MODOLO DIS".to_string(), location)),
+            _ => {
+                return Err(self.runtime_error(
+                    Kind::CantModolo,
+                    "CAN'T MODOLO DIS".to_string(),
+                    location,
+                ))
+            }
         })
     }
 
-    pub fn neg(&self, a: &Value, location: Pos) -> Result<Value, Errors> {
+    pub fn neg(&self, a: &Value, location: Span) -> Result<Value, Errors> {
+        #[cfg(feature = "bigint")]
+        if let Some(v) = Self::big_neg(a) {
+            return Ok(v);
+        }
+        if let Some(v) = Self::ratio_neg(a) {
+            return Ok(v);
+        }
         Ok(match a {
-            Int(v) => Int(-v),
+            Int(v) => match v.checked_neg() {
+                Some(v) => Int(v),
+                None => Self::wide(&Operator::Minus, 0, *v),
+            },
             Float(v) => Float(-v),
-            _ => return Err(self.runtime_error("CAN'T NEGATE DIS".to_string(), location)),
+            _ => {
+                return Err(self.runtime_error(
+                    Kind::CantNegate,
+                    "CAN'T NEGATE DIS".to_string(),
+                    location,
+                ))
+            }
         })
     }
+
+    // Exact numbers have no answer for dividing by 0, decimals keep their
+    // infinities
+    pub(super) fn by_zero(a: &Value, b: &Value) -> bool {
+        fn exact(v: &Value) -> bool {
+            match v {
+                Int(_) | Value::Ratio(_) => true,
+                #[cfg(feature = "bigint")]
+                Big(_) => true,
+                _ => false,
+            }
+        }
+        exact(a) && exact(b) && Self::float(b) == Some(0.0)
+    }
+
+    // Whole numbers too big for i128: exact with the bigint feature, the
+    // closest decimal without
+    #[cfg(feature = "bigint")]
+    pub(super) fn wide(op: &Operator, a: i128, b: i128) -> Value {
+        use crate::bigint::Big;
+        Self::exact(op, &Big::from(a), &Big::from(b))
+            .unwrap_or_else(|| Float(Self::floats(op, a as f64, b as f64)))
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    pub(super) fn wide(op: &Operator, a: i128, b: i128) -> Value {
+        Float(Self::floats(op, a as f64, b as f64))
+    }
+
+    pub(super) fn floats(op: &Operator, a: f64, b: f64) -> f64 {
+        match op {
+            Operator::Plus => a + b,
+            Operator::Minus => a - b,
+            Operator::Times => a * b,
+            Operator::Over => a / b,
+            Operator::Mod => a % b,
+            Operator::ToThe => a.powf(b),
+            Operator::Div => (a / b).trunc(),
+            _ => f64::NAN,
+        }
+    }
 }
@@ -0,0 +1,87 @@
+use std::cmp::Ordering;
+
+use super::*;
+
+// Whole numbers past i128, only with the bigint feature
+
+// Back to an Int whenever it fits
+impl From<Big> for Value {
+    fn from(n: Big) -> Self {
+        match n.to_i128() {
+            Some(n) => Value::Int(n),
+            None => Value::Big(n),
+        }
+    }
+}
+
+// Results bigger than this many bits turn into decimals instead, so a big
+// 'tothe' can't eat all the memory
+const MAX_BITS: u64 = 1 << 20;
+
+fn whole(a: &Value) -> Option<Big> {
+    match a {
+        Value::Int(n) => Some(Big::from(*n)),
+        Value::Big(n) => Some(n.clone()),
+        _ => None,
+    }
+}
+
+fn is_big(a: &Value) -> bool {
+    matches!(a, Value::Big(_))
+}
+
+impl<'a> Eval<'a> {
+    // With a Big on either side: exact with two whole numbers, decimal with
+    // a decimal. None if there's no Big or it's not a number.
+    pub(super) fn big_arith(op: &Operator, a: &Value, b: &Value) -> Option<Value> {
+        if !is_big(a) && !is_big(b) {
+            return None;
+        }
+        if let (Some(x), Some(y)) = (whole(a), whole(b)) {
+            if let Some(v) = Self::exact(op, &x, &y) {
+                return Some(v);
+            }
+        }
+        Some(Value::Float(Self::floats(op, Self::float(a)?, Self::float(b)?)))
+    }
+
+    // None if the answer isn't a whole number, or is way too big
+    pub(super) fn exact(op: &Operator, a: &Big, b: &Big) -> Option<Value> {
+        Some(Value::from(match op {
+            Operator::Plus => a + b,
+            Operator::Minus => a - b,
+            Operator::Times => a * b,
+            Operator::Mod if !b.is_zero() => a % b,
+            // Only if it's whole, the rest are decimals
+            Operator::Over if !b.is_zero() && (a % b).is_zero() => a / b,
+            Operator::Div if !b.is_zero() => a / b,
+            Operator::ToThe => {
+                let exp = u32::try_from(b.to_i128()?).ok()?;
+                if a.bits().saturating_mul(exp as u64) > MAX_BITS {
+                    return None;
+                }
+                a.pow(exp)
+            }
+            _ => return None,
+        }))
+    }
+
+    pub(super) fn big_neg(a: &Value) -> Option<Value> {
+        match a {
+            Value::Big(n) => Some(Value::from(-n)),
+            _ => None,
+        }
+    }
+
+    // Compares with a Big on either side, None if there's no Big or it's
+    // not a number
+    pub(super) fn big_cmp(a: &Value, b: &Value, test: fn(Ordering) -> bool) -> Option<bool> {
+        if !is_big(a) && !is_big(b) {
+            return None;
+        }
+        if let (Some(x), Some(y)) = (whole(a), whole(b)) {
+            return Some(test(x.cmp(&y)));
+        }
+        Some(Self::float(a)?.partial_cmp(&Self::float(b)?).is_some_and(test))
+    }
+}
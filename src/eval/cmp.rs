@@ -1,7 +1,16 @@
+use std::cmp::Ordering;
+
 use super::*;
 
 impl<'a> Eval<'a> {
     pub fn is(&self, a: &Value, b: &Value) -> Value {
+        #[cfg(feature = "bigint")]
+        if let Some(v) = Self::big_cmp(a, b, Ordering::is_eq) {
+            return Value::Choice(v);
+        }
+        if let Some(v) = Self::ratio_cmp(a, b, Ordering::is_eq) {
+            return Value::Choice(v);
+        }
         Value::Choice(match (a, b) {
             (Value::Int(a), Value::Int(b)) => a == b,
             (Value::Int(a), Value::Float(b)) => *a as f64 == *b,
@@ -38,47 +47,99 @@ impl<'a> Eval<'a> {
         self.not(&self.is(a, b))
     }
 
-    pub fn below(&self, a: &Value, b: &Value, location: Pos) -> Result<Value, Errors> {
+    pub fn below(&self, a: &Value, b: &Value, location: Span) -> Result<Value, Errors> {
+        #[cfg(feature = "bigint")]
+        if let Some(v) = Self::big_cmp(a, b, Ordering::is_lt) {
+            return Ok(Value::Choice(v));
+        }
+        if let Some(v) = Self::ratio_cmp(a, b, Ordering::is_lt) {
+            return Ok(Value::Choice(v));
+        }
         Ok(Value::Choice(match (a, b) {
             (Value::Int(a), Value::Int(b)) => a < b,
             (Value::Int(a), Value::Float(b)) => (*a as f64) < *b,
             (Value::Float(a), Value::Int(b)) => *a < (*b as f64),
             (Value::Float(a), Value::Float(b)) => a < b,
             (Value::Text(a), Value::Text(b)) => a < b,
-            _ => return Err(self.runtime_error("CAN'T COMPARE VALUES".to_string(), location)),
+            _ => {
+                return Err(self.runtime_error(
+                    Kind::CantCompare,
+                    "CAN'T COMPARE VALUES".to_string(),
+                    location,
+                ))
+            }
         }))
     }
 
-    pub fn above(&self, a: &Value, b: &Value, location: Pos) -> Result<Value, Errors> {
+    pub fn above(&self, a: &Value, b: &Value, location: Span) -> Result<Value, Errors> {
+        #[cfg(feature = "bigint")]
+        if let Some(v) = Self::big_cmp(a, b, Ordering::is_gt) {
+            return Ok(Value::Choice(v));
+        }
+        if let Some(v) = Self::ratio_cmp(a, b, Ordering::is_gt) {
+            return Ok(Value::Choice(v));
+        }
         Ok(Value::Choice(match (a, b) {
             (Value::Int(a), Value::Int(b)) => a > b,
             (Value::Int(a), Value::Float(b)) => (*a as f64) > *b,
             (Value::Float(a), Value::Int(b)) => *a > (*b as f64),
             (Value::Float(a), Value::Float(b)) => a > b,
             (Value::Text(a), Value::Text(b)) => a > b,
-            _ => return Err(self.runtime_error("CAN'T COMPARE VALUES".to_string(), location)),
+            _ => {
+                return Err(self.runtime_error(
+                    Kind::CantCompare,
+                    "CAN'T COMPARE VALUES".to_string(),
+                    location,
+                ))
+            }
         }))
     }
 
-    pub fn atmost(&self, a: &Value, b: &Value, location: Pos) -> Result<Value, Errors> {
+    pub fn atmost(&self, a: &Value, b: &Value, location: Span) -> Result<Value, Errors> {
+        #[cfg(feature = "bigint")]
+        if let Some(v) = Self::big_cmp(a, b, Ordering::is_le) {
+            return Ok(Value::Choice(v));
+        }
+        if let Some(v) = Self::ratio_cmp(a, b, Ordering::is_le) {
+            return Ok(Value::Choice(v));
+        }
         Ok(Value::Choice(match (a, b) {
             (Value::Int(a), Value::Int(b)) => a <= b,
             (Value::Int(a), Value::Float(b)) => (*a as f64) <= *b,
             (Value::Float(a), Value::Int(b)) => *a <= (*b as f64),
             (Value::Float(a), Value::Float(b)) => a <= b,
             (Value::Text(a), Value::Text(b)) => a <= b,
-            _ => return Err(self.runtime_error("CAN'T COMPARE VALUES".to_string(), location)),
+            _ => {
+                return Err(self.runtime_error(
+                    Kind::CantCompare,
+                    "CAN'T COMPARE VALUES".to_string(),
+                    location,
+                ))
+            }
         }))
     }
 
-    pub fn atleast(&self, a: &Value, b: &Value, location: Pos) -> Result<Value, Errors> {
+    pub fn atleast(&self, a: &Value, b: &Value, location: Span) -> Result<Value, Errors> {
+        #[cfg(feature = "bigint")]
+        if let Some(v) = Self::big_cmp(a, b, Ordering::is_ge) {
+            return Ok(Value::Choice(v));
+        }
+        if let Some(v) = Self::ratio_cmp(a, b, Ordering::is_ge) {
+            return Ok(Value::Choice(v));
+        }
         Ok(Value::Choice(match (a, b) {
             (Value::Int(a), Value::Int(b)) => a >= b,
             (Value::Int(a), Value::Float(b)) => (*a as f64) >= *b,
             (Value::Float(a), Value::Int(b)) => *a >= (*b as f64),
             (Value::Float(a), Value::Float(b)) => a >= b,
             (Value::Text(a), Value::Text(b)) => a >= b,
-            _ => return Err(self.runtime_error("CAN'T COMPARE VALUES".to_string(), location)),
+            _ => {
+                return Err(self.runtime_error(
+                    Kind::CantCompare,
+                    "CAN'T COMPARE VALUES".to_string(),
+                    location,
+                ))
+            }
         }))
     }
 }
@@ -1,14 +1,27 @@
 use super::*;
 impl<'a> Eval<'a> {
-    pub fn switch(&mut self, stmt: &Stmt, cond: &Expr, _line: usize) -> Result<(), Errors> {
+    pub fn switch(
+        &mut self,
+        stmt: &Stmt,
+        cond: &Expr,
+        otherwise: Option<&Stmt>,
+        _span: Span,
+    ) -> Result<(), Errors> {
+        let location = cond.span();
         let cond = self.eval_expr(cond)?;
-        if let Value::Choice(true) = self.choice(&cond) {
-            self.run_stmt(&stmt)?;
+        let yes = matches!(self.choice(&cond), Value::Choice(true));
+        self.hook_switch(location, yes);
+        if yes {
+            self.run_stmt(stmt)?;
+        } else if let Some(otherwise) = otherwise {
+            self.run_stmt(otherwise)?;
         }
         Ok(())
     }
 
-    pub fn run_from(&mut self, from: &Expr, to: &Expr, line: usize) -> Result<(), Errors> {
+    pub fn run_from(&mut self, from: &Expr, to: &Expr, _span: Span) -> Result<(), Errors> {
+        // The lines asked for, if they're wrong
+        let lines = from.span().to(to.span());
         let from = self.eval_expr(from)?;
         let to = self.eval_expr(to)?;
         if let (Value::Int(from), Value::Int(to)) = (from, to) {
@@ -16,20 +29,60 @@ impl<'a> Eval<'a> {
                 let from = from as usize;
                 let to = to as usize;
 
-                return Err(Errors::Interrupt(from, to));
+                return Err(Errors::Interrupt(from, to, 1));
             }
         }
-        return Err(self.runtime_error("INVALID LINE RANGE".to_string(), Pos(line, 1)));
+        Err(self.runtime_error(Kind::InvalidLineRange, "INVALID LINE RANGE".to_string(), lines))
     }
 
-    pub fn run_at(&mut self, at: &Expr, line: usize) -> Result<(), Errors> {
+    pub fn run_at(&mut self, at: &Expr, _span: Span) -> Result<(), Errors> {
+        let location = at.span();
         let at = self.eval_expr(at)?;
         if let Value::Int(at) = at {
             if at >= 1 && at <= self.input.len() as i128 {
                 let at = at as usize;
-                return Err(Errors::Interrupt(at, at));
+                return Err(Errors::Interrupt(at, at, 1));
             }
         }
-        return Err(self.runtime_error("INVALID LINE NUMBER".to_string(), Pos(line, 1)));
+        Err(self.runtime_error(
+            Kind::InvalidLineNumber,
+            "INVALID LINE NUMBER".to_string(),
+            location,
+        ))
+    }
+
+    // 'run ... repeat N', or 'repeat N' for the last 'run' again
+    pub fn repeat(&mut self, run: Option<&Stmt>, times: &Expr, span: Span) -> Result<(), Errors> {
+        let (from, to) = match run {
+            Some(run) => match self.run_stmt(run) {
+                Err(Errors::Interrupt(from, to, _)) => (from, to),
+                v => return v.map(|_| ()),
+            },
+            None => match self.last_run {
+                Some(lines) => lines,
+                None => {
+                    return Err(self.runtime_error(
+                        Kind::NothingToRepeat,
+                        "NOTHING TO REPEAT".to_string(),
+                        span,
+                    ))
+                }
+            },
+        };
+        let location = times.span();
+        let times = self.eval_expr(times)?;
+        Err(Errors::Interrupt(from, to, self.repeat_count(times, location)?))
+    }
+
+    // Span of the times, if they're wrong
+    pub fn repeat_count(&self, times: Value, location: Span) -> Result<u128, Errors> {
+        match times {
+            Value::Int(times) if times >= 0 => Ok(times as u128),
+            _ => Err(self.runtime_error(
+                Kind::CantRepeat,
+                "CAN'T REPEAT DIS".to_string(),
+                location,
+            )),
+        }
     }
 }
@@ -4,20 +4,32 @@ impl<'a> Eval<'a> {
         Value::Choice(match a {
             Value::Choice(a) => *a,
             Value::Int(a) => *a != 0,
+            // Never 0, that would be an Int
+            #[cfg(feature = "bigint")]
+            Value::Big(_) => true,
+            Value::Ratio(_) => true,
             Value::Float(a) => *a != 0.0,
-            Value::Text(a) => a.len() != 0,
+            Value::Text(a) => !a.is_empty(),
             Value::Nothing => false,
         })
     }
 
-    pub fn num(&self, a: &Value, _location: Pos) -> Value {
+    pub fn num(&self, a: &Value, _location: Span) -> Value {
         match a {
             Value::Choice(a) => Value::Int(if *a { 0 } else { 1 }),
             Value::Int(a) => Value::Int(*a),
+            #[cfg(feature = "bigint")]
+            Value::Big(a) => Value::Big(a.clone()),
+            Value::Ratio(a) => Value::Ratio(*a),
             Value::Float(a) => Value::Float(*a),
             Value::Text(a) => {
                 if let Ok(v) = a.parse::<i128>() {
                     Value::Int(v)
+                } else if let Some(v) = Self::big_text(a) {
+                    v
+                } else if let Some(v) = Ratio::parse(a) {
+                    // What 'text' makes of a fraction, like 1.05 or 1/3
+                    Value::from(v)
                 } else if let Ok(v) = a.parse::<f64>() {
                     Value::Float(v)
                 } else {
@@ -32,10 +44,24 @@ impl<'a> Eval<'a> {
     pub fn text(&self, a: &Value) -> Value {
         Value::Text(match a {
             Value::Int(n) => n.to_string(),
+            #[cfg(feature = "bigint")]
+            Value::Big(n) => n.to_string(),
+            Value::Ratio(n) => n.to_string(),
             Value::Float(n) => n.to_string(),
             Value::Text(s) => s.to_owned(),
             Value::Choice(b) => (if *b { "yes" } else { "no" }).to_string(),
             Value::Nothing => "nothing".to_string(),
         })
     }
+
+    // All the digits of a number too big for i128
+    #[cfg(feature = "bigint")]
+    fn big_text(a: &str) -> Option<Value> {
+        Big::parse(a).map(Value::from)
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    fn big_text(_a: &str) -> Option<Value> {
+        None
+    }
 }
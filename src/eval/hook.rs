@@ -0,0 +1,63 @@
+use super::*;
+
+/// Gets a look at the program between statements, e.g. the debugger,
+/// coverage or the profiler. Only [`Hook::line`] has to be written.
+pub trait Hook {
+    /// Before the statement on this line runs. An error stops the program.
+    fn line(&mut self, eval: &mut Eval, line: usize) -> Result<(), Errors>;
+
+    /// The condition of an `if`, written here, was checked.
+    fn switch(&mut self, _cond: Span, _yes: bool) {}
+
+    /// A `run` on this line started going through these lines.
+    fn run(&mut self, _line: usize, _from: usize, _to: usize) {}
+}
+
+// So the caller can keep it and look at it after the program is done
+impl<T: Hook + ?Sized> Hook for &mut T {
+    fn line(&mut self, eval: &mut Eval, line: usize) -> Result<(), Errors> {
+        (**self).line(eval, line)
+    }
+
+    fn switch(&mut self, cond: Span, yes: bool) {
+        (**self).switch(cond, yes)
+    }
+
+    fn run(&mut self, line: usize, from: usize, to: usize) {
+        (**self).run(line, from, to)
+    }
+}
+
+impl<'a> Eval<'a> {
+    /// Called as the program runs, until [`Eval::take_hook`].
+    pub fn set_hook(&mut self, hook: Box<dyn Hook + 'a>) {
+        self.hook = Some(hook);
+    }
+
+    /// Stops calling the hook and gives it back.
+    pub fn take_hook(&mut self) -> Option<Box<dyn Hook + 'a>> {
+        self.hook.take()
+    }
+
+    pub(super) fn before_line(&mut self, line: usize) -> Result<(), Errors> {
+        // Borrow checker won't let it see self while it's in self
+        let Some(mut hook) = self.hook.take() else {
+            return Ok(());
+        };
+        let ret = hook.line(self, line);
+        self.hook = Some(hook);
+        ret
+    }
+
+    pub(super) fn hook_switch(&mut self, cond: Span, yes: bool) {
+        if let Some(hook) = self.hook.as_mut() {
+            hook.switch(cond, yes);
+        }
+    }
+
+    pub(super) fn hook_run(&mut self, line: usize, from: usize, to: usize) {
+        if let Some(hook) = self.hook.as_mut() {
+            hook.run(line, from, to);
+        }
+    }
+}
@@ -1,27 +1,125 @@
+use std::{
+    collections::VecDeque,
+    io::{stdin, stdout, Write},
+};
+
 use super::*;
+
+/// Where `write` sends its text and `read` gets its lines from.
+pub trait Io {
+    /// One `write`, without the newline.
+    fn write_line(&mut self, text: &str) -> Result<(), Errors>;
+    /// One line for `read`, without the newline. Empty once there's no more.
+    fn read_line(&mut self) -> Result<String, Errors>;
+}
+
+// The terminal, unless the program is given something else
+pub struct Stdio;
+
+impl Io for Stdio {
+    fn write_line(&mut self, text: &str) -> Result<(), Errors> {
+        let mut out = stdout();
+        writeln!(out, "{}", text).map_err(|_| Errors::IOError)?;
+        out.flush().map_err(|_| Errors::IOError)
+    }
+
+    fn read_line(&mut self) -> Result<String, Errors> {
+        let mut buf = String::new();
+        stdin().read_line(&mut buf).map_err(|_| Errors::IOError)?;
+        Ok(buf.trim_end().to_owned())
+    }
+}
+
+// Input given up front and output kept, e.g. for tests
+#[derive(Debug, Default)]
+pub struct Buffer {
+    input: VecDeque<String>,
+    output: String,
+}
+
+impl Buffer {
+    // Each line of input is one 'read'
+    pub fn new(input: &str) -> Self {
+        Self {
+            input: input.lines().map(str::to_string).collect(),
+            output: String::new(),
+        }
+    }
+
+    // Everything written so far, a newline after each 'write'
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+}
+
+impl Io for Buffer {
+    fn write_line(&mut self, text: &str) -> Result<(), Errors> {
+        self.output.push_str(text);
+        self.output.push('\n');
+        Ok(())
+    }
+
+    fn read_line(&mut self) -> Result<String, Errors> {
+        Ok(self.input.pop_front().unwrap_or_default())
+    }
+}
+
+// A function for each, e.g. to show 'write's in a window
+pub struct Callback<W, R> {
+    write: W,
+    read: R,
+}
+
+impl<W: FnMut(&str), R: FnMut() -> String> Callback<W, R> {
+    pub fn new(write: W, read: R) -> Self {
+        Self { write, read }
+    }
+}
+
+impl<W: FnMut(&str), R: FnMut() -> String> Io for Callback<W, R> {
+    fn write_line(&mut self, text: &str) -> Result<(), Errors> {
+        (self.write)(text);
+        Ok(())
+    }
+
+    fn read_line(&mut self) -> Result<String, Errors> {
+        Ok((self.read)())
+    }
+}
+
+// So the caller can keep it and look at it after the program is done
+impl<T: Io + ?Sized> Io for &mut T {
+    fn write_line(&mut self, text: &str) -> Result<(), Errors> {
+        (**self).write_line(text)
+    }
+
+    fn read_line(&mut self) -> Result<String, Errors> {
+        (**self).read_line()
+    }
+}
+
 impl<'a> Eval<'a> {
-    pub fn write(&mut self, operand: &Expr, _line: usize) -> Result<(), Errors> {
+    pub fn write(&mut self, operand: &Expr, _span: Span) -> Result<(), Errors> {
         let operand = self.eval_expr(operand)?;
-        let mut formatted = if let Value::Text(s) = self.text(&operand) {
+        self.write_value(&operand)
+    }
+
+    pub fn write_value(&mut self, operand: &Value) -> Result<(), Errors> {
+        let formatted = if let Value::Text(s) = self.text(operand) {
             s
         } else {
             // Shouldn't execute
             String::new()
         };
-        formatted.push('\n');
-        stdout()
-            .write(formatted.as_bytes())
-            .map_err(|_| Errors::IOError)?;
+        self.io.write_line(&formatted)
+    }
 
-        stdout().flush().map_err(|_| Errors::IOError)?;
-        Ok(())
+    pub fn read(&mut self, _location: Span) -> Result<Value, Errors> {
+        Ok(Value::Text(self.io.read_line()?))
     }
 
-    pub fn read(&self, _location: Pos) -> Result<Value, Errors> {
-        let mut buf = String::new();
-        std::io::stdin()
-            .read_line(&mut buf)
-            .map_err(|_| Errors::IOError)?;
-        Ok(Value::Text(buf.trim_end().to_owned()))
+    /// Where `write` and `read` go instead of the terminal.
+    pub fn set_io(&mut self, io: Box<dyn Io + 'a>) {
+        self.io = io;
     }
 }
@@ -0,0 +1,60 @@
+use std::time::{Duration, Instant};
+
+use super::*;
+use crate::lexer::Pos;
+
+// Stops runaway programs, nothing is limited unless it's set
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Limits {
+    // Statements run
+    pub steps: Option<u64>,
+    // Since the program started
+    pub time: Option<Duration>,
+    // 'run's inside 'run's that haven't finished yet
+    pub depth: Option<usize>,
+}
+
+impl<'a> Eval<'a> {
+    /// Replaces the limits, which start counting when the program does.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    pub(super) fn start_limits(&mut self) {
+        self.steps = 0;
+        self.started = Instant::now();
+    }
+
+    // Before the statement on this line runs
+    pub(super) fn spend(&mut self, line: usize) -> Result<(), Errors> {
+        self.steps += 1;
+        if self.limits.steps.is_some_and(|max| self.steps > max) {
+            return Err(self.limit_error(Kind::OutOfSteps, "RAN OUT OF STEPS", line));
+        }
+        if self
+            .limits
+            .time
+            .is_some_and(|max| self.started.elapsed() > max)
+        {
+            return Err(self.limit_error(Kind::OutOfTime, "RAN OUT OF TIME", line));
+        }
+        Ok(())
+    }
+
+    // After a 'run' on this line
+    pub(super) fn check_depth(&self, line: usize) -> Result<(), Errors> {
+        match self.limits.depth {
+            Some(max) if self.call_stack.len() > max => {
+                Err(self.limit_error(Kind::TooDeep, "TOO MANY RUNS INSIDE RUNS", line))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // Points at the whole line, whatever part of it was running
+    fn limit_error(&self, kind: Kind, msg: &str, line: usize) -> Errors {
+        let text = Lexer::to_string_lossy(self.reader.get_line(line));
+        let span = Span::at(Pos(line, 1), text.chars().count());
+        self.runtime_error(kind, msg.to_string(), span)
+    }
+}
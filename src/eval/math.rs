@@ -0,0 +1,157 @@
+use super::*;
+use crate::ratio::Ratio;
+use Value::*;
+
+// Which way 'floor', 'ceil' and 'round' go
+#[derive(Clone, Copy)]
+enum Way {
+    Down,
+    Up,
+    // Halves go away from 0
+    Nearest,
+}
+
+impl<'a> Eval<'a> {
+    // Whole division, drops what 'mod' would give
+    pub fn div(&self, a: &Value, b: &Value, location: Span) -> Result<Value, Errors> {
+        if Self::by_zero(a, b) {
+            return Err(self.runtime_error(
+                Kind::DivideByZero,
+                "CAN'T DIVIDE BY ZERO".to_string(),
+                location,
+            ));
+        }
+        #[cfg(feature = "bigint")]
+        if let Some(v) = Self::big_arith(&Operator::Div, a, b) {
+            return Ok(v);
+        }
+        if let Some(v) = Self::ratio_arith(&Operator::Div, a, b) {
+            return Ok(v);
+        }
+        Ok(match (a, b) {
+            (Int(a), Int(b)) => match a.checked_div(*b) {
+                Some(v) => Int(v),
+                None => Self::wide(&Operator::Div, *a, *b),
+            },
+            (Int(a), Float(b)) => Float((*a as f64 / b).trunc()),
+            (Float(a), Int(b)) => Float((a / *b as f64).trunc()),
+            (Float(a), Float(b)) => Float((a / b).trunc()),
+            _ => {
+                return Err(self.runtime_error(
+                    Kind::CantDivide,
+                    "CAN'T DIVIDE DIS".to_string(),
+                    location,
+                ))
+            }
+        })
+    }
+
+    pub fn min(&self, a: &Value, b: &Value, location: Span) -> Result<Value, Errors> {
+        Ok(match self.below(b, a, location)? {
+            Choice(true) => b.clone(),
+            _ => a.clone(),
+        })
+    }
+
+    pub fn max(&self, a: &Value, b: &Value, location: Span) -> Result<Value, Errors> {
+        Ok(match self.above(b, a, location)? {
+            Choice(true) => b.clone(),
+            _ => a.clone(),
+        })
+    }
+
+    pub fn floor(&self, a: &Value, location: Span) -> Result<Value, Errors> {
+        self.whole(a, Way::Down, location)
+    }
+
+    pub fn ceil(&self, a: &Value, location: Span) -> Result<Value, Errors> {
+        self.whole(a, Way::Up, location)
+    }
+
+    pub fn round(&self, a: &Value, location: Span) -> Result<Value, Errors> {
+        self.whole(a, Way::Nearest, location)
+    }
+
+    pub fn abs(&self, a: &Value, location: Span) -> Result<Value, Errors> {
+        match Self::float(a) {
+            Some(v) if v < 0.0 => self.neg(a, location),
+            Some(_) => Ok(a.clone()),
+            None => Err(self.runtime_error(Kind::CantAbs, "CAN'T ABS DIS".to_string(), location)),
+        }
+    }
+
+    // Exact if the answer is, a decimal if not
+    pub fn sqrt(&self, a: &Value, location: Span) -> Result<Value, Errors> {
+        let v = match Self::float(a) {
+            Some(v) if v >= 0.0 => v,
+            _ => {
+                return Err(self.runtime_error(
+                    Kind::CantSqrt,
+                    "CAN'T SQRT DIS".to_string(),
+                    location,
+                ))
+            }
+        };
+        Ok(match a {
+            Int(n) => root(*n).map_or(Float(v.sqrt()), Int),
+            #[cfg(feature = "bigint")]
+            Big(n) => {
+                let root = n.sqrt();
+                match &(&root * &root) == n {
+                    true => Value::from(root),
+                    false => Float(v.sqrt()),
+                }
+            }
+            Ratio(n) => match (root(n.num()), root(n.den())) {
+                (Some(num), Some(den)) => Ratio::new(num, den).map_or(Float(v.sqrt()), Value::from),
+                _ => Float(v.sqrt()),
+            },
+            _ => Float(v.sqrt()),
+        })
+    }
+
+    // Always a whole number, unless a decimal is too big for one
+    fn whole(&self, a: &Value, way: Way, location: Span) -> Result<Value, Errors> {
+        Ok(match a {
+            Int(_) => a.clone(),
+            #[cfg(feature = "bigint")]
+            Big(_) => a.clone(),
+            Ratio(n) => {
+                let (num, den) = (n.num(), n.den());
+                let (down, left) = (num.div_euclid(den), num.rem_euclid(den));
+                // Not whole, so the one above can't overflow
+                let up = match way {
+                    Way::Down => false,
+                    Way::Up => true,
+                    Way::Nearest => left > den - left || (left == den - left && num > 0),
+                };
+                Int(down + up as i128)
+            }
+            Float(v) => {
+                let v = match way {
+                    Way::Down => v.floor(),
+                    Way::Up => v.ceil(),
+                    Way::Nearest => v.round(),
+                };
+                // i128 goes up to just below 2^127
+                match v.abs() < 2f64.powi(127) {
+                    true => Int(v as i128),
+                    false => Float(v),
+                }
+            }
+            _ => {
+                return Err(self.runtime_error(
+                    Kind::CantRound,
+                    "CAN'T ROUND DIS".to_string(),
+                    location,
+                ))
+            }
+        })
+    }
+}
+
+// Square root of a whole number, if it's whole too
+fn root(n: i128) -> Option<i128> {
+    let root = n.unsigned_abs().isqrt() as i128;
+    (root * root == n).then_some(root)
+}
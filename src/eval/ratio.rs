@@ -0,0 +1,84 @@
+use std::cmp::Ordering;
+
+use super::*;
+
+// Fractions, when whole numbers are divided or written with 'dot'
+
+// Back to an Int whenever it's whole
+impl From<Ratio> for Value {
+    fn from(n: Ratio) -> Self {
+        match n.is_whole() {
+            true => Value::Int(n.num()),
+            false => Value::Ratio(n),
+        }
+    }
+}
+
+fn exact(a: &Value) -> Option<Ratio> {
+    match a {
+        Value::Int(n) => Some(Ratio::whole(*n)),
+        Value::Ratio(n) => Some(*n),
+        _ => None,
+    }
+}
+
+impl<'a> Eval<'a> {
+    // With a Ratio on either side: exact with whole numbers and fractions,
+    // decimal with a decimal or if it gets too big. None if there's no Ratio
+    // or it's not a number.
+    pub(super) fn ratio_arith(op: &Operator, a: &Value, b: &Value) -> Option<Value> {
+        if !matches!(a, Value::Ratio(_)) && !matches!(b, Value::Ratio(_)) {
+            return None;
+        }
+        if let (Some(x), Some(y)) = (exact(a), exact(b)) {
+            if let Some(v) = Self::fraction(op, &x, &y) {
+                return Some(Value::from(v));
+            }
+        }
+        Some(Value::Float(Self::floats(op, Self::float(a)?, Self::float(b)?)))
+    }
+
+    pub(super) fn fraction(op: &Operator, a: &Ratio, b: &Ratio) -> Option<Ratio> {
+        match op {
+            Operator::Plus => a.checked_add(b),
+            Operator::Minus => a.checked_sub(b),
+            Operator::Times => a.checked_mul(b),
+            Operator::Over => a.checked_div(b),
+            Operator::Mod => a.checked_rem(b),
+            Operator::ToThe if b.is_whole() => a.checked_pow(b.num()),
+            Operator::Div => a.checked_div(b).map(|v| Ratio::whole(v.num() / v.den())),
+            _ => None,
+        }
+    }
+
+    pub(super) fn ratio_neg(a: &Value) -> Option<Value> {
+        match a {
+            Value::Ratio(n) => n.checked_neg().map(Value::Ratio),
+            _ => None,
+        }
+    }
+
+    // Compares with a Ratio on either side, None if there's no Ratio or it's
+    // not a number
+    pub(super) fn ratio_cmp(a: &Value, b: &Value, test: fn(Ordering) -> bool) -> Option<bool> {
+        if !matches!(a, Value::Ratio(_)) && !matches!(b, Value::Ratio(_)) {
+            return None;
+        }
+        if let (Some(x), Some(y)) = (exact(a), exact(b)) {
+            return Some(test(x.cmp(&y)));
+        }
+        Some(Self::float(a)?.partial_cmp(&Self::float(b)?).is_some_and(test))
+    }
+
+    // Any number as a decimal
+    pub(super) fn float(a: &Value) -> Option<f64> {
+        match a {
+            Value::Int(n) => Some(*n as f64),
+            #[cfg(feature = "bigint")]
+            Value::Big(n) => Some(n.to_f64()),
+            Value::Ratio(n) => Some(n.to_f64()),
+            Value::Float(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use super::*;
+use Value::*;
+
+// Letters count from 1, like lines do. Letters are characters, so é is one.
+impl<'a> Eval<'a> {
+    pub fn length(&self, a: &Value, location: Span) -> Result<Value, Errors> {
+        match a {
+            Text(s) => Ok(Int(s.chars().count() as i128)),
+            _ => Err(self.runtime_error(
+                Kind::CantMeasure,
+                "CAN'T MEASURE DIS".to_string(),
+                location,
+            )),
+        }
+    }
+
+    pub fn letter(&self, at: &Value, a: &Value, location: Span) -> Result<Value, Errors> {
+        let s = self.cut(a, location)?;
+        let letter = match at {
+            Int(at) => usize::try_from(*at)
+                .ok()
+                .and_then(|at| at.checked_sub(1))
+                .and_then(|at| s.chars().nth(at)),
+            _ => None,
+        };
+        match letter {
+            Some(c) => Ok(Text(c.to_string())),
+            None => Err(self.runtime_error(
+                Kind::InvalidLetterNumber,
+                "INVALID LETTER NUMBER".to_string(),
+                location,
+            )),
+        }
+    }
+
+    // Letters from to to, both in. 'part 3 to 2' is empty.
+    pub fn part(
+        &self,
+        from: &Value,
+        to: &Value,
+        a: &Value,
+        location: Span,
+    ) -> Result<Value, Errors> {
+        let s = self.cut(a, location)?;
+        let len = s.chars().count() as i128;
+        match (from, to) {
+            (Int(from), Int(to)) if *to <= len && *from >= 1 && *from <= *to + 1 => Ok(Text(
+                s.chars()
+                    .skip(*from as usize - 1)
+                    .take((*to - *from + 1) as usize)
+                    .collect(),
+            )),
+            _ => Err(self.runtime_error(
+                Kind::InvalidLetterRange,
+                "INVALID LETTER RANGE".to_string(),
+                location,
+            )),
+        }
+    }
+
+    // Where the first one starts, 0 if it's not there
+    pub fn find(&self, what: &Value, a: &Value, location: Span) -> Result<Value, Errors> {
+        match (what, a) {
+            (Text(what), Text(s)) => Ok(Int(match s.find(what.as_str()) {
+                Some(i) => s[..i].chars().count() as i128 + 1,
+                None => 0,
+            })),
+            _ => Err(self.runtime_error(
+                Kind::CantSearch,
+                "CAN'T SEARCH DIS".to_string(),
+                location,
+            )),
+        }
+    }
+
+    pub fn upper(&self, a: &Value, location: Span) -> Result<Value, Errors> {
+        match a {
+            Text(s) => Ok(Text(s.to_uppercase())),
+            _ => Err(self.runtime_error(
+                Kind::CantChangeCase,
+                "CAN'T CHANGE CASE OF DIS".to_string(),
+                location,
+            )),
+        }
+    }
+
+    pub fn lower(&self, a: &Value, location: Span) -> Result<Value, Errors> {
+        match a {
+            Text(s) => Ok(Text(s.to_lowercase())),
+            _ => Err(self.runtime_error(
+                Kind::CantChangeCase,
+                "CAN'T CHANGE CASE OF DIS".to_string(),
+                location,
+            )),
+        }
+    }
+
+    // Unicode number of the first letter, 'code of "A"' is 65
+    pub fn code(&self, a: &Value, location: Span) -> Result<Value, Errors> {
+        match a {
+            Text(s) if !s.is_empty() => Ok(Int(s.chars().next().map_or(0, |c| c as i128))),
+            _ => Err(self.runtime_error(
+                Kind::CantGetCode,
+                "CAN'T GET CODE OF DIS".to_string(),
+                location,
+            )),
+        }
+    }
+
+    // The other way around, 'character 65' is "A"
+    pub fn character(&self, a: &Value, location: Span) -> Result<Value, Errors> {
+        match a {
+            Int(n) => match u32::try_from(*n).ok().and_then(char::from_u32) {
+                Some(c) => Ok(Text(c.to_string())),
+                None => Err(self.runtime_error(
+                    Kind::InvalidCharacterCode,
+                    "INVALID CHARACTER CODE".to_string(),
+                    location,
+                )),
+            },
+            _ => Err(self.runtime_error(
+                Kind::InvalidCharacterCode,
+                "INVALID CHARACTER CODE".to_string(),
+                location,
+            )),
+        }
+    }
+
+    // The text being cut up by 'letter' and 'part'
+    fn cut<'v>(&self, a: &'v Value, location: Span) -> Result<&'v str, Errors> {
+        match a {
+            Text(s) => Ok(s),
+            _ => Err(self.runtime_error(Kind::CantCut, "CAN'T CUT DIS".to_string(), location)),
+        }
+    }
+}
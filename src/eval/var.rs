@@ -1,6 +1,6 @@
 use super::*;
 impl<'a> Eval<'a> {
-    pub fn asgn(&mut self, left: &Expr, right: &Expr, _line: usize) -> Result<(), Errors>  {
+    pub fn asgn(&mut self, left: &Expr, right: &Expr, _span: Span) -> Result<(), Errors>  {
         let right = self.eval_expr(right)?;
         let Expr::Id(id, _location) = left else {
             panic!("LEFT SIDE NOT ID");
@@ -10,10 +10,14 @@ impl<'a> Eval<'a> {
         Ok(())
     }
 
-    pub fn id(&mut self, name: &Name, location: Pos) -> Result<Value, Errors> {
+    pub fn id(&mut self, name: &Name, location: Span) -> Result<Value, Errors> {
         match self.context.get(name) {
             Some(value) => Ok(value.clone()),
-            None => Err(self.runtime_error(format!("VARIABLE {} NOT FOUND", name.id), location)),
+            None => Err(self.runtime_error(
+                Kind::VariableNotFound,
+                format!("VARIABLE {} NOT FOUND", name.id),
+                location,
+            )),
         }
     }
 }
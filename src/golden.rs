@@ -0,0 +1,191 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    coverage::{Coverage, FileCoverage},
+    errors::Errors,
+    eval::{Buffer, Eval},
+    lexer::Reader,
+    vm,
+};
+
+// Golden files: prog.noob runs with prog.in as its input (none if it's
+// missing), then what it wrote has to match prog.out and the error it ended
+// with has to match prog.err. A missing .out or .err expects nothing.
+
+#[derive(Debug)]
+pub enum Outcome {
+    Pass,
+    // The differences, with - for expected and + for what happened
+    Fail(Vec<String>),
+    // The expectations were rewritten
+    Blessed,
+}
+
+// What a program did
+#[derive(Debug, PartialEq)]
+pub struct Run {
+    pub output: String,
+    // The error as it's printed, empty if there wasn't one
+    pub error: String,
+}
+
+// Every .noob file in the paths, going into directories, in order
+pub fn find(paths: &[PathBuf]) -> Result<Vec<PathBuf>, Errors> {
+    let mut found = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let mut entries = fs::read_dir(path)
+                .map_err(|_| Errors::IOError)?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| Errors::IOError)?;
+            entries.sort();
+            let (dirs, files): (Vec<_>, Vec<_>) = entries.into_iter().partition(|p| p.is_dir());
+            found.extend(files.into_iter().filter(|p| is_noob(p)));
+            found.extend(find(&dirs)?);
+        } else if path.is_file() {
+            found.push(path.clone());
+        } else {
+            return Err(Errors::ArgumentError(format!(
+                "CAN'T FIND {}",
+                path.display()
+            )));
+        }
+    }
+    Ok(found)
+}
+
+fn is_noob(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "noob")
+}
+
+// Runs the program with its .in and no terminal, adding to the coverage if
+// there is one (the vm can't keep track)
+pub fn run(path: &Path, use_vm: bool, coverage: Option<&mut Coverage>) -> Result<Run, Errors> {
+    let source = fs::read(path).map_err(|_| Errors::IOError)?;
+    let input = read_or_empty(&path.with_extension("in"))?;
+    let mut io = Buffer::new(&input);
+
+    let mut reader = Reader::from(source);
+    let result = crate::parse(&mut reader).and_then(|ast| {
+        if use_vm {
+            let chunk = vm::compile(&ast);
+            let mut vm = vm::Vm::new(&chunk, &ast, &reader);
+            vm.set_io(Box::new(&mut io));
+            vm.run_prog()
+        } else {
+            let mut lines = FileCoverage::new(&ast);
+            let mut eval = Eval::new(&ast, &reader);
+            eval.set_io(Box::new(&mut io));
+            if coverage.is_some() {
+                eval.set_hook(Box::new(&mut lines));
+            }
+            let result = eval.run_prog();
+            drop(eval);
+            if let Some(coverage) = coverage {
+                coverage.add(&path.display().to_string(), lines);
+            }
+            result
+        }
+    });
+
+    Ok(Run {
+        output: io.output().to_string(),
+        error: match result {
+            Ok(_) => String::new(),
+            Err(e) => e.to_string(),
+        },
+    })
+}
+
+// Runs it and compares, or with bless writes down what it did instead
+pub fn check(
+    path: &Path,
+    use_vm: bool,
+    bless: bool,
+    coverage: Option<&mut Coverage>,
+) -> Result<Outcome, Errors> {
+    let actual = run(path, use_vm, coverage)?;
+    let (out, err) = (path.with_extension("out"), path.with_extension("err"));
+
+    if bless {
+        fs::write(&out, &actual.output).map_err(|_| Errors::IOError)?;
+        if actual.error.is_empty() {
+            if err.exists() {
+                fs::remove_file(&err).map_err(|_| Errors::IOError)?;
+            }
+        } else {
+            fs::write(&err, &actual.error).map_err(|_| Errors::IOError)?;
+        }
+        return Ok(Outcome::Blessed);
+    }
+
+    let expected = Run {
+        output: read_or_empty(&out)?,
+        error: read_or_empty(&err)?,
+    };
+    if expected == actual {
+        return Ok(Outcome::Pass);
+    }
+
+    let mut lines = Vec::new();
+    if expected.output != actual.output {
+        lines.push("OUTPUT:".to_string());
+        lines.extend(diff(&expected.output, &actual.output));
+    }
+    if expected.error != actual.error {
+        lines.push("ERROR:".to_string());
+        lines.extend(diff(&expected.error, &actual.error));
+    }
+    Ok(Outcome::Fail(lines))
+}
+
+fn read_or_empty(path: &Path) -> Result<String, Errors> {
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(text),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(_) => Err(Errors::IOError),
+    }
+}
+
+// Line by line, keeping the longest run of lines both have in common
+pub fn diff(expected: &str, actual: &str) -> Vec<String> {
+    let a: Vec<&str> = expected.lines().collect();
+    let b: Vec<&str> = actual.lines().collect();
+
+    // common[i][j]: lines in common between a[i..] and b[j..]
+    let mut common = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            common[i][j] = if a[i] == b[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            lines.push(format!("  {}", a[i]));
+            (i, j) = (i + 1, j + 1);
+        } else if j == b.len() || (i < a.len() && common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(format!("- {}", a[i]));
+            i += 1;
+        } else {
+            lines.push(format!("+ {}", b[j]));
+            j += 1;
+        }
+    }
+
+    // Same lines, so it's the newline at the end
+    if a == b {
+        lines.push("(DIFFERENT NEWLINE AT THE END)".to_string());
+    }
+    lines
+}
@@ -0,0 +1,357 @@
+use std::{
+    fmt::{self, Display},
+    io::{BufRead, Read, Write},
+};
+
+use crate::errors::Errors;
+
+// Just enough JSON for the editor protocols
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    // Keeps the order keys were added in
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn object(fields: Vec<(&str, Json)>) -> Self {
+        Json::Object(
+            fields
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect(),
+        )
+    }
+
+    pub fn get(&self, key: &str) -> &Json {
+        match self {
+            Json::Object(fields) => fields
+                .iter()
+                .find(|(k, _)| k == key)
+                .map_or(&Json::Null, |(_, v)| v),
+            _ => &Json::Null,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_usize(&self) -> Option<usize> {
+        self.as_f64()
+            .filter(|n| *n >= 0.0 && n.fract() == 0.0)
+            .map(|n| n as usize)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Json::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> &[Json] {
+        match self {
+            Json::Array(items) => items,
+            _ => &[],
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Json> {
+        let mut parser = JsonParser {
+            input: s.as_bytes(),
+            pt: 0,
+        };
+        let v = parser.value()?;
+        parser.spaces();
+        (parser.pt == parser.input.len()).then_some(v)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Json::String(s.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(s: String) -> Self {
+        Json::String(s)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(n: usize) -> Self {
+        Json::Number(n as f64)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(b: bool) -> Self {
+        Json::Bool(b)
+    }
+}
+
+impl From<Vec<Json>> for Json {
+    fn from(items: Vec<Json>) -> Self {
+        Json::Array(items)
+    }
+}
+
+impl Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Number(n) if n.is_finite() => write!(f, "{}", n),
+            Json::Number(_) => write!(f, "null"),
+            Json::String(s) => write_string(f, s),
+            Json::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (k, v)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, k)?;
+                    write!(f, ":{}", v)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+pub enum Message {
+    Json(Json),
+    // Without a length, or not JSON. It's skipped so the session can go on.
+    Garbled,
+}
+
+// Content-Length framed messages, how both editor protocols talk.
+// None once the other side hangs up.
+pub fn read_message(input: &mut dyn BufRead) -> Result<Option<Message>, Errors> {
+    let mut len = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header).map_err(|_| Errors::IOError)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        // Anything before it is what's left of a message without a length
+        if let Some((_, n)) = header.split_once("Content-Length:") {
+            len = n.trim().parse::<u64>().ok();
+        }
+    }
+    let Some(len) = len else {
+        return Ok(Some(Message::Garbled));
+    };
+
+    let mut body = Vec::new();
+    input
+        .take(len)
+        .read_to_end(&mut body)
+        .map_err(|_| Errors::IOError)?;
+    // Hung up in the middle of it
+    if (body.len() as u64) < len {
+        return Err(Errors::IOError);
+    }
+    let json = String::from_utf8(body).ok().and_then(|body| Json::parse(&body));
+    Ok(Some(json.map_or(Message::Garbled, Message::Json)))
+}
+
+pub fn write_message(output: &mut dyn Write, message: &Json) -> Result<(), Errors> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)
+        .and_then(|_| output.flush())
+        .map_err(|_| Errors::IOError)
+}
+
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+struct JsonParser<'a> {
+    input: &'a [u8],
+    pt: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pt).copied()
+    }
+
+    fn spaces(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pt += 1;
+        }
+    }
+
+    fn eat(&mut self, s: &str) -> Option<()> {
+        if self.input[self.pt..].starts_with(s.as_bytes()) {
+            self.pt += s.len();
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    fn value(&mut self) -> Option<Json> {
+        self.spaces();
+        match self.peek()? {
+            b'n' => self.eat("null").map(|_| Json::Null),
+            b't' => self.eat("true").map(|_| Json::Bool(true)),
+            b'f' => self.eat("false").map(|_| Json::Bool(false)),
+            b'"' => self.string().map(Json::String),
+            b'[' => self.array(),
+            b'{' => self.object(),
+            _ => self.number(),
+        }
+    }
+
+    fn number(&mut self) -> Option<Json> {
+        let start = self.pt;
+        while let Some(b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E') = self.peek() {
+            self.pt += 1;
+        }
+        let s = std::str::from_utf8(&self.input[start..self.pt]).ok()?;
+        s.parse().ok().map(Json::Number)
+    }
+
+    fn hex4(&mut self) -> Option<u32> {
+        let s = std::str::from_utf8(self.input.get(self.pt..self.pt + 4)?).ok()?;
+        self.pt += 4;
+        u32::from_str_radix(s, 16).ok()
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.pt += 1;
+        let mut s = Vec::new();
+        loop {
+            match self.peek()? {
+                b'"' => {
+                    self.pt += 1;
+                    return String::from_utf8(s).ok();
+                }
+                b'\\' => {
+                    self.pt += 1;
+                    let c = self.peek()?;
+                    self.pt += 1;
+                    let c = match c {
+                        b'n' => '\n',
+                        b't' => '\t',
+                        b'r' => '\r',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'u' => {
+                            let mut code = self.hex4()?;
+                            // Surrogate pair
+                            if (0xd800..0xdc00).contains(&code) {
+                                self.eat("\\u")?;
+                                let low = self.hex4()?;
+                                code = 0x10000
+                                    + ((code - 0xd800) << 10)
+                                    + (low.checked_sub(0xdc00)?);
+                            }
+                            char::from_u32(code)?
+                        }
+                        c => c as char,
+                    };
+                    let mut buf = [0; 4];
+                    s.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                c => {
+                    s.push(c);
+                    self.pt += 1;
+                }
+            }
+        }
+    }
+
+    fn array(&mut self) -> Option<Json> {
+        self.pt += 1;
+        let mut items = Vec::new();
+        self.spaces();
+        if self.eat("]").is_some() {
+            return Some(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.spaces();
+            match self.peek()? {
+                b',' => self.pt += 1,
+                b']' => {
+                    self.pt += 1;
+                    return Some(Json::Array(items));
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    fn object(&mut self) -> Option<Json> {
+        self.pt += 1;
+        let mut fields = Vec::new();
+        self.spaces();
+        if self.eat("}").is_some() {
+            return Some(Json::Object(fields));
+        }
+        loop {
+            self.spaces();
+            if self.peek()? != b'"' {
+                return None;
+            }
+            let key = self.string()?;
+            self.spaces();
+            self.eat(":")?;
+            fields.push((key, self.value()?));
+            self.spaces();
+            match self.peek()? {
+                b',' => self.pt += 1,
+                b'}' => {
+                    self.pt += 1;
+                    return Some(Json::Object(fields));
+                }
+                _ => return None,
+            }
+        }
+    }
+}
@@ -1,19 +1,40 @@
-use std::{
-    cmp,
-    fs::File,
-    io::{BufReader, Read},
-};
+use std::{cmp, io::Read};
 
 use crate::{
-    errors::Errors,
+    errors::{Errors, Kind},
     token::{Token, TokenType},
 };
 
-// location in code
-#[derive(Debug, Clone, Copy)]
+// location in code: line, character in the line (both from 1)
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Pos(pub usize, pub usize);
 
-// bytes reader
+// From a location up to another one, which isn't included
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span(pub Pos, pub Pos);
+
+impl Span {
+    // Some characters of one line
+    pub fn at(pos: Pos, len: usize) -> Span {
+        Span(pos, Pos(pos.0, pos.1 + len))
+    }
+
+    // Where it starts
+    pub fn line(&self) -> usize {
+        self.0 .0
+    }
+
+    // From the start of this one to the end of the other
+    pub fn to(self, other: Span) -> Span {
+        Span(self.0, other.1)
+    }
+}
+
+/// The source as bytes, read one at a time by the lexer. Columns count
+/// characters, so a tab or an emoji is one.
+///
+/// Lines are found while lexing, so [`Reader::get_line`] only knows about
+/// the ones the lexer has been through.
 pub struct Reader {
     input: Vec<u8>,
     // I dumb
@@ -24,20 +45,27 @@ pub struct Reader {
 }
 
 impl Reader {
-    pub fn new(mut reader: BufReader<File>) -> Result<Self, Errors> {
+    /// Reads all of anything readable, e.g. a file or stdin.
+    pub fn new(mut reader: impl Read) -> Result<Self, Errors> {
         let mut input = Vec::new();
         reader
             .read_to_end(&mut input)
             .map_err(|_| Errors::IOError)?;
-        Ok(Self {
+        Ok(Self::from_bytes(input))
+    }
+
+    /// The source as it is, it doesn't have to be UTF-8.
+    pub fn from_bytes(input: Vec<u8>) -> Self {
+        Self {
             input,
             newlines: Vec::new(),
             pt: 0,
             col: 1,
             line: 1,
-        })
+        }
     }
 
+    /// Where the next byte is.
     pub fn pos(&self) -> Pos {
         Pos(self.line, self.col)
     }
@@ -48,13 +76,23 @@ impl Reader {
         &self.input[prev..self.pt]
     }
 
+    /// The next byte, moving past it.
     pub fn readc(&mut self) -> u8 {
         let c = self.peek();
         self.pt += 1;
-        self.col += 1;
+        // Not the middle of a UTF-8 character
+        if c & 0xc0 != 0x80 {
+            self.col += 1;
+        }
         c
     }
 
+    /// Everything not read yet.
+    pub fn rest(&self) -> &[u8] {
+        &self.input[self.pt.min(self.input.len())..]
+    }
+
+    /// The next byte without moving, 26 (end of file) after the last one.
     pub fn peek(&mut self) -> u8 {
         if self.pt >= self.input.len() {
             return 26;
@@ -62,19 +100,36 @@ impl Reader {
         self.input[self.pt]
     }
 
+    /// The whole UTF-8 character here, None at the end or if it's broken.
+    pub fn peek_char(&self) -> Option<char> {
+        let len = match self.input.get(self.pt)? {
+            0x00..=0x7f => 1,
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            _ => 4,
+        };
+        let bytes = self.input.get(self.pt..self.pt + len)?;
+        std::str::from_utf8(bytes).ok()?.chars().next()
+    }
+
+    /// Moves past n bytes, or to the end.
     pub fn skip(&mut self, n: usize) {
         let prev = self.pt;
         self.pt = cmp::min(self.input.len(), self.pt + n);
-        self.col += self.pt - prev;
+        self.col += Self::chars(&self.input[prev..self.pt]);
     }
 
     pub fn _back(&mut self, n: usize) {
         let prev = self.pt;
         self.pt = cmp::max(0, self.pt - n);
-        self.col -= prev - self.pt;
+        self.col -= Self::chars(&self.input[self.pt..prev]);
     }
 
-    // Consume newline character
+    fn chars(bytes: &[u8]) -> usize {
+        bytes.iter().filter(|c| *c & 0xc0 != 0x80).count()
+    }
+
+    /// Moves past the newline here, onto the next line.
     pub fn newline(&mut self) {
         self.newlines.push(self.pt);
         self.line += 1;
@@ -82,7 +137,8 @@ impl Reader {
         self.pt += 1;
     }
 
-    // Used in lexer
+    /// The line being read, without its newline. Reads the rest of it if
+    /// the lexer isn't there yet.
     pub fn get_this_line(&mut self) -> &[u8] {
         let start = if self.line > 1 {
             self.newlines[self.line - 2] + 1
@@ -103,7 +159,8 @@ impl Reader {
         &self.input[start..end]
     }
 
-    // Used in parser, eval etc
+    /// A line the lexer has been through, without its newline, e.g. to show
+    /// with an error.
     pub fn get_line(&self, line: usize) -> &[u8] {
         let start = if line > 1 {
             self.newlines[line - 2] + 1
@@ -122,6 +179,18 @@ impl Reader {
     }
 }
 
+impl From<&str> for Reader {
+    fn from(source: &str) -> Self {
+        Self::from_bytes(source.as_bytes().to_vec())
+    }
+}
+
+impl From<Vec<u8>> for Reader {
+    fn from(input: Vec<u8>) -> Self {
+        Self::from_bytes(input)
+    }
+}
+
 pub struct Lexer<'a> {
     reader: &'a mut Reader,
 }
@@ -131,17 +200,45 @@ impl<'a> Lexer<'a> {
         Self { reader }
     }
     pub fn lex(&mut self) -> Result<Vec<Token>, Errors> {
+        let (tokens, errors) = self.lex_all();
+        match Errors::combine(errors) {
+            Some(e) => Err(e),
+            None => Ok(tokens),
+        }
+    }
+
+    // Keeps going after an error, the line it's on comes out empty
+    pub fn lex_all(&mut self) -> (Vec<Token>, Vec<Errors>) {
         let mut tokens = Vec::new();
+        let mut errors = Vec::new();
+        let mut line_start = 0;
         loop {
-            let tok = self.scan_token()?;
-            if TokenType::Eof == tok.token_type {
-                // Freaking borrow checker
-                tokens.push(tok);
-                break;
+            let tok = match self.scan_token() {
+                Ok(tok) => tok,
+                Err(e) => {
+                    errors.push(e);
+                    tokens.truncate(line_start);
+                    // A lone \r counts as junk too
+                    while !matches!(self.reader.peek(), b'\n' | 26) {
+                        self.reader.readc();
+                    }
+                    continue;
+                }
+            };
+            match tok.token_type {
+                TokenType::Eof => {
+                    // Freaking borrow checker
+                    tokens.push(tok);
+                    break;
+                }
+                TokenType::Lf => {
+                    tokens.push(tok);
+                    line_start = tokens.len();
+                }
+                _ => tokens.push(tok),
             }
-            tokens.push(tok);
         }
-        Ok(tokens)
+        (tokens, errors)
     }
 
     fn scan_token(&mut self) -> Result<Token, Errors> {
@@ -152,54 +249,161 @@ impl<'a> Lexer<'a> {
             b'\n' | b'\r' => self.scan_newline()?,
             26 => self.scan_eof(),
             c if c.is_ascii_digit() => self.scan_number()?,
-            c if c.is_ascii_alphabetic() => self.scan_word()?,
+            _ if self.reader.peek_char().is_some_and(char::is_alphabetic) => self.scan_word()?,
             c => {
-                return Err(
-                    self.syntax_error(format!("WEIRD SYMBOL: {}", c as char), self.reader.pos())
-                );
+                let c = self.reader.peek_char().unwrap_or(c as char);
+                let span = Span::at(self.reader.pos(), 1);
+                return Err(self.syntax_error(
+                    Kind::WeirdSymbol,
+                    format!("WEIRD SYMBOL: {}", c),
+                    span,
+                ));
             }
         })
     }
 
+    // Starts with a letter, then letters and digits from any language
     fn scan_word(&mut self) -> Result<Token, Errors> {
-        let mut s = Vec::new();
+        let mut s = String::new();
         let pos = self.reader.pos();
-        while self.reader.peek().is_ascii_alphabetic() {
-            s.push(self.reader.readc());
+        while let Some(c) = self.reader.peek_char().filter(|c| c.is_alphanumeric()) {
+            self.reader.skip(c.len_utf8());
+            s.push(c);
         }
 
-        let s = Self::to_string_lossy(&s);
-        // Is keyword
-        if let Some(token) = Token::from_str(&s, pos) {
+        // Is keyword, only if the whole word is one: 'note2' is a name
+        // and not a comment
+        let span = Span(pos, self.reader.pos());
+        if let Some(token) = Token::from_str(&s, span) {
             // Read until newline if comment
             if TokenType::Note == token.token_type {
                 while !Lexer::is_eol(self.reader.peek()) {
                     self.reader.readc();
                 }
+                // The last line doesn't need a newline
+                if self.reader.peek() == 26 {
+                    return Ok(self.scan_eof());
+                }
                 return self.scan_newline();
             }
             return Ok(token);
         }
 
         // Is identifier
-        Ok(Token::new(TokenType::Id(s), pos))
+        Ok(Token::new(TokenType::Id(s), span))
     }
 
+    // "text" on one line, or """text""" over as many as it needs.
+    // Escapes are words between backslashes: \quote\ \newline\ \tab\
+    // \backslash\ and \unicode 1F600\
     fn scan_string(&mut self) -> Result<Token, Errors> {
         let pos = self.reader.pos();
-        self.reader.skip(1);
+        let multiline = self.reader.rest().starts_with(b"\"\"\"");
+        if multiline {
+            self.reader.skip(3);
+            // Text starts on the next line if nothing's after the quotes
+            self.skip_newline();
+        } else {
+            self.reader.skip(1);
+        }
+
         let mut s = Vec::new();
-        while self.reader.peek() != b'"' {
-            if Self::is_eol(self.reader.peek()) {
-                return Err(self.syntax_error("MISSING QUOTE".to_string(), self.reader.pos()));
+        // Keep going after a bad escape so the rest of the text isn't read as
+        // code, the error is made at the end since that reads the whole line
+        let mut error = None;
+        loop {
+            match self.reader.peek() {
+                b'"' if !multiline => break,
+                b'"' if self.reader.rest().starts_with(b"\"\"\"") => break,
+                b'\\' => match self.scan_escape() {
+                    Ok(c) => s.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+                    Err(e) => {
+                        error.get_or_insert(e);
+                    }
+                },
+                26 if multiline => {
+                    error.get_or_insert((
+                        Kind::MissingQuotes,
+                        "MISSING QUOTES".to_string(),
+                        Span::at(pos, 3),
+                    ));
+                    break;
+                }
+                // On its own it isn't a line end, but it's read over so the
+                // text can still end
+                b'\r' if multiline && !self.reader.rest().starts_with(b"\r\n") => {
+                    let span = Span::at(self.reader.pos(), 1);
+                    error.get_or_insert((
+                        Kind::CarriageWithoutReturn,
+                        "CARRIAGE WITHOUT RETURN".to_string(),
+                        span,
+                    ));
+                    self.reader.readc();
+                }
+                b'\r' | b'\n' if multiline => {
+                    self.skip_newline();
+                    s.push(b'\n');
+                }
+                c if Self::is_eol(c) => {
+                    // All of the text that never ended
+                    let span = Span(pos, self.reader.pos());
+                    error.get_or_insert((Kind::MissingQuote, "MISSING QUOTE".to_string(), span));
+                    break;
+                }
+                _ => s.push(self.reader.readc()),
             }
-            s.push(self.reader.readc());
         }
 
-        let s = Self::to_string_lossy(&s);
+        match error {
+            Some((kind, msg, span)) => Err(self.syntax_error(kind, msg, span)),
+            None => {
+                self.reader.skip(if multiline { 3 } else { 1 });
+                let span = Span(pos, self.reader.pos());
+                Ok(Token::new(TokenType::String(Self::to_string_lossy(&s)), span))
+            }
+        }
+    }
 
+    // Message and where, for scan_string to report
+    fn scan_escape(&mut self) -> Result<char, (Kind, String, Span)> {
+        let pos = self.reader.pos();
         self.reader.skip(1);
-        Ok(Token::new(TokenType::String(s), pos))
+        let mut word = Vec::new();
+        while self.reader.peek() != b'\\' {
+            if Self::is_eol(self.reader.peek()) || self.reader.peek() == b'"' {
+                return Err((
+                    Kind::EscapeNeverEnds,
+                    "ESCAPE NEVER ENDS".to_string(),
+                    Span(pos, self.reader.pos()),
+                ));
+            }
+            word.push(self.reader.readc());
+        }
+        self.reader.skip(1);
+        let span = Span(pos, self.reader.pos());
+
+        let word = Self::to_string_lossy(&word);
+        match word.split_once(' ') {
+            None if word == "quote" => Ok('"'),
+            None if word == "newline" => Ok('\n'),
+            None if word == "tab" => Ok('\t'),
+            None if word == "backslash" => Ok('\\'),
+            Some(("unicode", code)) => u32::from_str_radix(code.trim(), 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or((Kind::InvalidUnicode, format!("INVALID UNICODE: {}", code.trim()), span)),
+            _ => Err((Kind::WeirdEscape, format!("WEIRD ESCAPE: \\{}\\", word), span)),
+        }
+    }
+
+    // Inside multiline text, \r\n or \n
+    fn skip_newline(&mut self) {
+        if self.reader.rest().starts_with(b"\r\n") {
+            self.reader.skip(1);
+        }
+        if self.reader.peek() == b'\n' {
+            self.reader.newline();
+        }
     }
 
     fn scan_number(&mut self) -> Result<Token, Errors> {
@@ -211,15 +415,21 @@ impl<'a> Lexer<'a> {
 
         let s = Self::to_string_lossy(&s);
         let num = s.parse();
+        let span = Span(pos, self.reader.pos());
 
         // range of i128 is [-2^127, 2^127-1]
         if let Ok(num) = num {
             if num < 1 << 127 {
-                return Ok(Token::new(TokenType::Number(num), pos));
+                return Ok(Token::new(TokenType::Number(num), span));
             }
         }
 
-        Err(self.syntax_error("NUMBER TOO LARGE".to_string(), pos))?
+        #[cfg(feature = "bigint")]
+        if let Some(num) = crate::bigint::Big::parse(&s) {
+            return Ok(Token::new(TokenType::BigNumber(num), span));
+        }
+
+        Err(self.syntax_error(Kind::NumberTooLarge, "NUMBER TOO LARGE".to_string(), span))?
     }
 
     fn scan_newline(&mut self) -> Result<Token, Errors> {
@@ -229,17 +439,22 @@ impl<'a> Lexer<'a> {
         }
 
         if self.reader.peek() != b'\n' {
-            return Err(self.syntax_error("CARRIAGE WITHOUT RETURN".to_string(), pos));
+            return Err(self.syntax_error(
+                Kind::CarriageWithoutReturn,
+                "CARRIAGE WITHOUT RETURN".to_string(),
+                Span::at(pos, 1),
+            ));
         }
 
         // Skip \n
+        let span = Span::at(pos, 1);
         self.reader.newline();
 
-        Ok(Token::new(TokenType::Lf, pos))
+        Ok(Token::new(TokenType::Lf, span))
     }
 
     fn scan_eof(&mut self) -> Token {
-        let token = Token::new(TokenType::Eof, self.reader.pos());
+        let token = Token::new(TokenType::Eof, Span::at(self.reader.pos(), 0));
         self.reader.skip(1);
         token
     }
@@ -251,14 +466,17 @@ impl<'a> Lexer<'a> {
     }
 
     fn is_eol(c: u8) -> bool {
-        match c {
-            b'\r' | b'\n' | 26 => true,
-            _ => false,
-        }
+        matches!(c, b'\r' | b'\n' | 26)
     }
 
-    fn syntax_error(&mut self, msg: String, pos: Pos) -> Errors {
-        Errors::SyntaxError(msg, pos, Self::to_string_lossy(self.reader.get_this_line()))
+    fn syntax_error(&mut self, kind: Kind, msg: String, span: Span) -> Errors {
+        // Multiline text can end after the line with the error
+        let line = if span.line() < self.reader.pos().0 {
+            self.reader.get_line(span.line())
+        } else {
+            self.reader.get_this_line()
+        };
+        Errors::SyntaxError(msg, span, Self::to_string_lossy(line), kind)
     }
 
     // &[u8] to String
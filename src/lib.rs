@@ -0,0 +1,98 @@
+//! The nooblang interpreter as a library.
+//!
+//! A program goes through the same stages the `nooblang-rust` binary uses:
+//!
+//! 1. [`Reader`] holds the raw source and keeps track of lines,
+//! 2. [`lexer::Lexer`] turns it into [`token::Token`]s,
+//! 3. [`parser::Parser`] turns those into one [`ast::Stmt`] per line,
+//! 4. [`Eval`] runs the statements, or [`vm::compile`] turns them into bytecode
+//!    for [`vm::Vm`] to run.
+//!
+//! With the `bigint` feature whole numbers never overflow: past `i128` they
+//! become [`bigint::Big`] instead of decimals.
+//!
+//! Whole numbers divided with `over` or written with `dot` stay exact as
+//! [`ratio::Ratio`] fractions, as long as they fit in `i128`.
+//!
+//! [`parse`] and [`run`] glue the stages together for the common case:
+//!
+//! ```no_run
+//! use std::{fs::File, io::BufReader};
+//!
+//! let file = BufReader::new(File::open("examples/prime.noob").unwrap());
+//! let mut reader = nooblang_rust::Reader::new(file).unwrap();
+//! let value = nooblang_rust::run(&mut reader).unwrap();
+//! println!("{}", nooblang_rust::Eval::display(&value));
+//! ```
+//!
+//! `write` and `read` go through an [`eval::Io`], the terminal unless
+//! [`Eval::set_io`] says otherwise. [`eval::Buffer`] feeds scripted input and
+//! keeps the output, [`eval::Callback`] hands both to closures:
+//!
+//! ```
+//! use nooblang_rust::{eval::Buffer, Eval, Reader};
+//!
+//! let mut reader = Reader::from("x be num read\nwrite x times 2");
+//! let ast = nooblang_rust::parse(&mut reader).unwrap();
+//! let mut io = Buffer::new("21\n");
+//! let mut eval = Eval::new(&ast, &reader);
+//! eval.set_io(Box::new(&mut io));
+//! eval.run_prog().unwrap();
+//! drop(eval);
+//! assert_eq!(io.output(), "42\n");
+//! ```
+
+pub mod ast;
+#[cfg(feature = "bigint")]
+pub mod bigint;
+pub mod coverage;
+pub mod dap;
+pub mod debugger;
+pub mod diagnostic;
+pub mod errors;
+pub mod eval;
+pub mod golden;
+pub mod json;
+pub mod lexer;
+pub mod lsp;
+pub mod parser;
+pub mod profiler;
+pub mod ratio;
+pub mod renumber;
+pub mod repl;
+pub mod token;
+pub mod vm;
+
+pub use errors::Errors;
+pub use eval::{Eval, Value};
+pub use lexer::Reader;
+
+use ast::Stmt;
+
+/// Lexes and parses the whole source in `reader` into one statement per line.
+///
+/// Every syntax error in the file is reported, as [`Errors::Multiple`] if
+/// there is more than one.
+pub fn parse(reader: &mut Reader) -> Result<Vec<Stmt>, Errors> {
+    let (prog, errors) = parse_all(reader);
+    match Errors::combine(errors) {
+        Some(e) => Err(e),
+        None => Ok(prog),
+    }
+}
+
+/// Like [`parse`], but always returns a program: lines with syntax errors
+/// become [`Stmt::Blank`] so the rest keep their line numbers.
+pub fn parse_all(reader: &mut Reader) -> (Vec<Stmt>, Vec<Errors>) {
+    let (tokens, mut errors) = lexer::Lexer::new(reader).lex_all();
+    let (prog, parse_errors) = parser::Parser::new(&tokens, reader).parse_all();
+    errors.extend(parse_errors);
+    (prog, errors)
+}
+
+/// Parses and evaluates a program, returning the value of the last statement run.
+pub fn run(reader: &mut Reader) -> Result<Value, Errors> {
+    let ast = parse(reader)?;
+    let mut eval = Eval::new(&ast, reader);
+    eval.run_prog()
+}
@@ -0,0 +1,506 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, Write},
+};
+
+use crate::{
+    ast::*,
+    diagnostic::Diagnostic,
+    errors::Errors,
+    json::{self, Json, Message},
+    lexer::{Lexer, Pos, Reader, Span},
+    parser::Parser,
+    token::KEYWORDS,
+};
+
+// Language Server Protocol over any pair of streams, stdio for editors.
+// Documents are synced whole and reparsed on every change.
+
+struct Server<'a> {
+    input: Box<dyn BufRead + 'a>,
+    output: Box<dyn Write + 'a>,
+    // Uri -> text
+    docs: HashMap<String, String>,
+}
+
+// What a parsed document knows
+struct Doc<'a> {
+    lines: Vec<&'a str>,
+    prog: Vec<Stmt>,
+    // Name -> line
+    labels: HashMap<String, usize>,
+    errors: Vec<Errors>,
+}
+
+// Something in the source the cursor can be on
+struct Symbol<'a> {
+    span: Span,
+    kind: Kind<'a>,
+}
+
+enum Kind<'a> {
+    // Name, whether it's being assigned with 'be'
+    Var(&'a str, bool),
+    // A constant 'run' target
+    Line(usize),
+}
+
+// Lines shown when hovering a 'run'
+const MAX_PREVIEW: usize = 20;
+
+pub fn serve<'a>(input: impl BufRead + 'a, output: impl Write + 'a) -> Result<(), Errors> {
+    let mut server = Server {
+        input: Box::new(input),
+        output: Box::new(output),
+        docs: HashMap::new(),
+    };
+
+    while let Some(message) = json::read_message(&mut server.input)? {
+        let Message::Json(message) = message else {
+            // Parse error, with no id since there's no telling what it was
+            let error = Json::object(vec![
+                ("code", Json::Number(-32700.0)),
+                ("message", "CAN'T READ DIS MESSAGE".into()),
+            ]);
+            let answer = vec![("jsonrpc", "2.0".into()), ("id", Json::Null), ("error", error)];
+            json::write_message(&mut server.output, &Json::object(answer))?;
+            continue;
+        };
+        let method = message.get("method").as_str().unwrap_or_default();
+        let params = message.get("params");
+        let id = message.get("id");
+
+        let result = match method {
+            "initialize" => Some(Json::object(vec![
+                (
+                    "capabilities",
+                    Json::object(vec![
+                        // Whole document on every change
+                        ("textDocumentSync", 1.into()),
+                        ("completionProvider", Json::object(vec![])),
+                        ("hoverProvider", true.into()),
+                        ("definitionProvider", true.into()),
+                        ("referencesProvider", true.into()),
+                    ]),
+                ),
+                (
+                    "serverInfo",
+                    Json::object(vec![("name", "nooblang-lsp".into())]),
+                ),
+            ])),
+            "shutdown" => Some(Json::Null),
+            "exit" => return Ok(()),
+            "textDocument/didOpen" => {
+                let doc = params.get("textDocument");
+                let uri = doc.get("uri").as_str().unwrap_or_default();
+                let text = doc.get("text").as_str().unwrap_or_default();
+                server.open(uri, text.to_string())?;
+                None
+            }
+            "textDocument/didChange" => {
+                let uri = params.get("textDocument").get("uri");
+                let uri = uri.as_str().unwrap_or_default();
+                // Full sync, so the last change is the whole text
+                if let Some(change) = params.get("contentChanges").as_array().last() {
+                    let text = change.get("text").as_str().unwrap_or_default();
+                    server.open(uri, text.to_string())?;
+                }
+                None
+            }
+            "textDocument/didClose" => {
+                let uri = params.get("textDocument").get("uri");
+                let uri = uri.as_str().unwrap_or_default();
+                server.docs.remove(uri);
+                server.publish(uri, Vec::new())?;
+                None
+            }
+            "textDocument/completion" => Some(server.completion(params)),
+            "textDocument/hover" => Some(server.hover(params)),
+            "textDocument/definition" => Some(server.definition(params)),
+            "textDocument/references" => Some(server.references(params)),
+            _ => None,
+        };
+
+        // Notifications have no id and get no answer
+        if *id == Json::Null {
+            continue;
+        }
+        let mut fields = vec![("jsonrpc", "2.0".into()), ("id", id.clone())];
+        match result {
+            Some(result) => fields.push(("result", result)),
+            None => fields.push((
+                "error",
+                Json::object(vec![
+                    ("code", Json::Number(-32601.0)),
+                    ("message", format!("WHAT IS {}", method).into()),
+                ]),
+            )),
+        }
+        json::write_message(&mut server.output, &Json::object(fields))?;
+    }
+    Ok(())
+}
+
+impl<'a> Server<'a> {
+    fn open(&mut self, uri: &str, text: String) -> Result<(), Errors> {
+        let doc = Doc::parse(&text);
+        let diagnostics = doc
+            .errors
+            .iter()
+            .map(|e| {
+                let d = Diagnostic::from_error(e);
+                let (start, end) = d.span;
+                Json::object(vec![
+                    (
+                        "range",
+                        range(
+                            doc.line_text(d.line.max(1)),
+                            d.line.max(1),
+                            start.max(1),
+                            end.max(1),
+                        ),
+                    ),
+                    ("severity", 1.into()),
+                    ("code", d.code.into()),
+                    ("source", "nooblang".into()),
+                    ("message", d.message.into()),
+                ])
+            })
+            .collect();
+        self.docs.insert(uri.to_string(), text);
+        self.publish(uri, diagnostics)
+    }
+
+    fn publish(&mut self, uri: &str, diagnostics: Vec<Json>) -> Result<(), Errors> {
+        let message = Json::object(vec![
+            ("jsonrpc", "2.0".into()),
+            ("method", "textDocument/publishDiagnostics".into()),
+            (
+                "params",
+                Json::object(vec![
+                    ("uri", uri.into()),
+                    ("diagnostics", diagnostics.into()),
+                ]),
+            ),
+        ]);
+        json::write_message(&mut self.output, &message)
+    }
+
+    // The document and the 1-based line and column a request is about
+    fn at<'p>(&self, params: &'p Json) -> Option<(&'p str, usize, usize)> {
+        let uri = params.get("textDocument").get("uri").as_str()?;
+        let text = self.docs.get(uri)?;
+        let position = params.get("position");
+        let line = position.get("line").as_usize()?;
+        let character = position.get("character").as_usize()?;
+        let line_text = text.split('\n').nth(line)?;
+        Some((uri, line + 1, char_col(line_text, character)))
+    }
+
+    fn completion(&self, params: &Json) -> Json {
+        // Keyword
+        let mut items: Vec<Json> = KEYWORDS
+            .iter()
+            .map(|(keyword, _)| completion_item(keyword, 14))
+            .collect();
+
+        let uri = params.get("textDocument").get("uri").as_str();
+        if let Some(doc) = uri
+            .and_then(|uri| self.docs.get(uri))
+            .map(|t| Doc::parse(t))
+        {
+            let mut names: Vec<&str> = Vec::new();
+            for stmt in &doc.prog {
+                for symbol in doc.symbols(stmt) {
+                    if let Kind::Var(name, _) = symbol.kind {
+                        if !names.contains(&name) {
+                            names.push(name);
+                        }
+                    }
+                }
+            }
+            // Variable
+            items.extend(names.into_iter().map(|name| completion_item(name, 6)));
+            // Reference
+            items.extend(doc.labels.keys().map(|name| completion_item(name, 18)));
+        }
+        items.into()
+    }
+
+    fn hover(&self, params: &Json) -> Json {
+        let Some((uri, line, _)) = self.at(params) else {
+            return Json::Null;
+        };
+        let doc = Doc::parse(&self.docs[uri]);
+        let Some(run) = doc.prog.get(line - 1).and_then(run_of) else {
+            return Json::Null;
+        };
+
+        let value = match doc.run_lines(run) {
+            Some((from, to)) => {
+                let what = if from == to {
+                    format!("runs line {}", from)
+                } else {
+                    format!("runs lines {} to {}", from, to)
+                };
+                // Only the lines that are there, targets can be anything
+                let (first, last) = (from.max(1), to.min(doc.lines.len()));
+                let mut shown: Vec<String> = (first..=last)
+                    .take(MAX_PREVIEW)
+                    .map(|n| format!("{:>4} | {}", n, doc.line_text(n)))
+                    .collect();
+                if shown.is_empty() {
+                    return hover_text(what);
+                }
+                if last - first >= MAX_PREVIEW {
+                    shown.push("     ...".to_string());
+                }
+                format!("{}\n```\n{}\n```", what, shown.join("\n"))
+            }
+            None => "runs lines worked out when it runs".to_string(),
+        };
+        hover_text(value)
+    }
+
+    fn definition(&self, params: &Json) -> Json {
+        let Some((uri, line, col)) = self.at(params) else {
+            return Json::Null;
+        };
+        let doc = Doc::parse(&self.docs[uri]);
+        let Some(stmt) = doc.prog.get(line - 1) else {
+            return Json::Null;
+        };
+
+        let lines = match doc.symbol_at(stmt, col).map(|s| s.kind) {
+            Some(Kind::Line(target)) => vec![target],
+            // Wherever it gets a value
+            Some(Kind::Var(name, _)) => {
+                let sites = doc
+                    .symbols_named(name)
+                    .filter(|s| matches!(s.kind, Kind::Var(_, true)));
+                return sites
+                    .map(|s| doc.location(uri, &s))
+                    .collect::<Vec<_>>()
+                    .into();
+            }
+            // Anywhere else on a 'run' goes to where it starts
+            None => match run_of(stmt).and_then(|run| doc.run_lines(run)) {
+                Some((from, _)) => vec![from],
+                None => return Json::Null,
+            },
+        };
+        lines
+            .into_iter()
+            .filter(|n| (1..=doc.lines.len()).contains(n))
+            .map(|n| location(uri, range(doc.line_text(n), n, 1, 1)))
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    fn references(&self, params: &Json) -> Json {
+        let Some((uri, line, col)) = self.at(params) else {
+            return Json::Null;
+        };
+        let doc = Doc::parse(&self.docs[uri]);
+        let declarations = params
+            .get("context")
+            .get("includeDeclaration")
+            .as_bool()
+            .unwrap_or(true);
+
+        let symbol = doc
+            .prog
+            .get(line - 1)
+            .and_then(|stmt| doc.symbol_at(stmt, col));
+        let Some(Kind::Var(name, _)) = symbol.map(|s| s.kind) else {
+            return Json::Null;
+        };
+        doc.symbols_named(name)
+            .filter(|s| declarations || matches!(s.kind, Kind::Var(_, false)))
+            .map(|s| doc.location(uri, &s))
+            .collect::<Vec<_>>()
+            .into()
+    }
+}
+
+impl<'a> Doc<'a> {
+    // Lines with syntax errors are left blank
+    fn parse(text: &'a str) -> Self {
+        let mut reader = Reader::from(text);
+        let (tokens, mut errors) = Lexer::new(&mut reader).lex_all();
+        let mut parser = Parser::new(&tokens, &reader);
+        let (prog, parse_errors) = parser.parse_all();
+        errors.extend(parse_errors);
+        Self {
+            lines: text.split('\n').map(|l| l.trim_end_matches('\r')).collect(),
+            labels: parser.labels().clone(),
+            prog,
+            errors,
+        }
+    }
+
+    fn line_text(&self, line: usize) -> &'a str {
+        line.checked_sub(1)
+            .and_then(|i| self.lines.get(i))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn location(&self, uri: &str, symbol: &Symbol) -> Json {
+        let Span(Pos(line, start), Pos(_, end)) = symbol.span;
+        location(uri, range(self.line_text(line), line, start, end))
+    }
+
+    // Constant lines a 'run' goes to
+    fn run_lines(&self, run: &Stmt) -> Option<(usize, usize)> {
+        match run {
+            Stmt::RunFrom(from, to, _) => Some((target(from)?, target(to)?)),
+            Stmt::RunAt(at, _) => target(at).map(|at| (at, at)),
+            _ => None,
+        }
+    }
+
+    fn symbol_at<'s>(&self, stmt: &'s Stmt, col: usize) -> Option<Symbol<'s>> {
+        self.symbols(stmt)
+            .into_iter()
+            .find(|s| (s.span.0 .1..=s.span.1 .1).contains(&col))
+    }
+
+    fn symbols_named<'s>(&'s self, name: &'s str) -> impl Iterator<Item = Symbol<'s>> + 's {
+        self.prog
+            .iter()
+            .flat_map(|stmt| self.symbols(stmt))
+            .filter(move |s| matches!(s.kind, Kind::Var(n, _) if n == name))
+    }
+
+    fn symbols<'s>(&self, stmt: &'s Stmt) -> Vec<Symbol<'s>> {
+        let mut symbols = Vec::new();
+        match stmt {
+            Stmt::Asgn(left, right, _) => {
+                if let Expr::Id(name, span) = left.as_ref() {
+                    symbols.push(Symbol {
+                        span: *span,
+                        kind: Kind::Var(name, true),
+                    });
+                }
+                self.expr_symbols(right, &mut symbols);
+            }
+            Stmt::RunFrom(from, to, _) => {
+                self.target_symbols(from, &mut symbols);
+                self.target_symbols(to, &mut symbols);
+            }
+            Stmt::RunAt(at, _) => self.target_symbols(at, &mut symbols),
+            Stmt::Write(expr, _) | Stmt::Expr(expr) => self.expr_symbols(expr, &mut symbols),
+            Stmt::Repeat(run, times, _) => {
+                if let Some(run) = run {
+                    symbols.extend(self.symbols(run));
+                }
+                self.expr_symbols(times, &mut symbols);
+            }
+            Stmt::Switch(stmt, cond, otherwise, _) => {
+                symbols.extend(self.symbols(stmt));
+                self.expr_symbols(cond, &mut symbols);
+                if let Some(otherwise) = otherwise {
+                    symbols.extend(self.symbols(otherwise));
+                }
+            }
+            Stmt::Blank | Stmt::End => (),
+        }
+        symbols
+    }
+
+    fn target_symbols<'s>(&self, expr: &'s Expr, symbols: &mut Vec<Symbol<'s>>) {
+        match target(expr) {
+            Some(line) => symbols.push(Symbol {
+                span: expr.span(),
+                kind: Kind::Line(line),
+            }),
+            None => self.expr_symbols(expr, symbols),
+        }
+    }
+
+    fn expr_symbols<'s>(&self, expr: &'s Expr, symbols: &mut Vec<Symbol<'s>>) {
+        match expr {
+            Expr::Id(name, span) => symbols.push(Symbol {
+                span: *span,
+                kind: Kind::Var(name, false),
+            }),
+            Expr::Binary(_, left, right, _) => {
+                self.expr_symbols(left, symbols);
+                self.expr_symbols(right, symbols);
+            }
+            Expr::Ternary(_, first, second, third, _) => {
+                self.expr_symbols(first, symbols);
+                self.expr_symbols(second, symbols);
+                self.expr_symbols(third, symbols);
+            }
+            Expr::Unary(_, operand, _) => self.expr_symbols(operand, symbols),
+            Expr::Nullary(..) | Expr::Literal(..) => (),
+        }
+    }
+}
+
+// The 'run' in a line, if it has one
+fn run_of(stmt: &Stmt) -> Option<&Stmt> {
+    match stmt {
+        Stmt::RunFrom(..) | Stmt::RunAt(..) => Some(stmt),
+        Stmt::Repeat(run, ..) => run.as_deref().and_then(run_of),
+        Stmt::Switch(stmt, _, otherwise, _) => {
+            run_of(stmt).or_else(|| otherwise.as_deref().and_then(run_of))
+        }
+        _ => None,
+    }
+}
+
+fn target(expr: &Expr) -> Option<usize> {
+    match expr {
+        Expr::Literal(Literal::Integer(n), _) => usize::try_from(*n).ok(),
+        Expr::Literal(Literal::Label(_, line), _) => Some(*line),
+        _ => None,
+    }
+}
+
+fn hover_text(value: String) -> Json {
+    Json::object(vec![(
+        "contents",
+        Json::object(vec![("kind", "markdown".into()), ("value", value.into())]),
+    )])
+}
+
+fn completion_item(label: &str, kind: usize) -> Json {
+    Json::object(vec![("label", label.into()), ("kind", kind.into())])
+}
+
+fn location(uri: &str, range: Json) -> Json {
+    Json::object(vec![("uri", uri.into()), ("range", range)])
+}
+
+// LSP positions count UTF-16 units from 0, we count characters from 1
+fn range(line_text: &str, line: usize, start: usize, end: usize) -> Json {
+    let position = |col: usize| {
+        Json::object(vec![
+            ("line", (line - 1).into()),
+            ("character", utf16_col(line_text, col).into()),
+        ])
+    };
+    Json::object(vec![("start", position(start)), ("end", position(end))])
+}
+
+fn utf16_col(line_text: &str, col: usize) -> usize {
+    line_text
+        .chars()
+        .take(col - 1)
+        .map(char::len_utf16)
+        .sum()
+}
+
+fn char_col(line_text: &str, character: usize) -> usize {
+    let mut units = 0;
+    for (i, c) in line_text.chars().enumerate() {
+        if units >= character {
+            return i + 1;
+        }
+        units += c.len_utf16();
+    }
+    line_text.chars().count() + 1
+}
@@ -1,17 +1,40 @@
-use std::{fs::File, io::BufReader};
+use std::{
+    fs::{self, File},
+    io::{stdin, BufReader},
+    path::PathBuf,
+    time::Duration,
+};
 
-use errors::Errors;
-
-mod ast;
-mod errors;
-mod eval;
-mod lexer;
-mod parser;
-mod token;
+use nooblang_rust::{
+    coverage::{Coverage, FileCoverage},
+    debugger::Debugger,
+    diagnostic::Diagnostic,
+    eval,
+    golden::{self, Outcome},
+    lexer, parser,
+    profiler::Profiler,
+    renumber::{self, Edit},
+    repl::Repl,
+    vm, Errors,
+};
 
 fn main() {
-    match run_file() {
+    // No arguments means interactive mode
+    let result = match std::env::args().nth(1).as_deref() {
+        None => Repl::new().run(),
+        Some("renumber") => renumber_file(),
+        Some("test") => test_files(),
+        Some(_) => run_file(),
+    };
+
+    match result {
         Ok(_) => (),
+        // For CI and editors, one object per line
+        Err(e) if std::env::args().any(|arg| arg == "--error-format=json") => {
+            for diagnostic in Diagnostic::all(&e) {
+                eprintln!("{}", diagnostic.to_json());
+            }
+        }
         Err(e) => {
             eprintln!("{}", e);
         }
@@ -19,13 +42,69 @@ fn main() {
 }
 
 fn run_file() -> Result<(), Errors> {
-    let filename = std::env::args().nth(1).ok_or(Errors::ArgumentError)?;
-    let f = BufReader::new(File::open(filename).map_err(|_| Errors::IOError)?);
-    let mut stream = lexer::Reader::new(f)?;
+    let mut args = std::env::args().skip(1).peekable();
+    let mut use_vm = false;
+    let mut debug = false;
+    let mut limits = eval::Limits::default();
+    let mut profile = None;
+    let mut lcov = None;
+    while let Some(flag) = args.next_if(|arg| arg.starts_with("--")) {
+        let what = || Errors::ArgumentError(format!("WHAT IS {}", flag));
+        match flag.split_once('=') {
+            Some(("--max-steps", n)) => limits.steps = Some(n.parse().map_err(|_| what())?),
+            Some(("--max-time", secs)) => {
+                let secs = secs.parse().map_err(|_| what())?;
+                limits.time = Some(Duration::try_from_secs_f64(secs).map_err(|_| what())?);
+            }
+            Some(("--max-depth", n)) => limits.depth = Some(n.parse().map_err(|_| what())?),
+            Some(("--coverage", file)) => lcov = Some(PathBuf::from(file)),
+            _ => match flag.as_str() {
+                "--vm" => use_vm = true,
+                "--debug" => debug = true,
+                "--profile" | "--profile=listing" => profile = Some("listing"),
+                "--profile=json" => profile = Some("json"),
+                "--profile=collapsed" => profile = Some("collapsed"),
+                // Used by main when printing the error
+                "--error-format=human" | "--error-format=json" => (),
+                _ => return Err(what()),
+            },
+        }
+    }
+
+    if use_vm && debug {
+        return Err(Errors::ArgumentError("CAN'T DEBUG THE VM".to_string()));
+    }
+    if profile.is_some() && (use_vm || debug) {
+        return Err(Errors::ArgumentError(
+            "CAN'T PROFILE THE VM OR WHILE DEBUGGING".to_string(),
+        ));
+    }
+    let limited = limits.steps.is_some() || limits.time.is_some() || limits.depth.is_some();
+    if use_vm && limited {
+        return Err(Errors::ArgumentError("CAN'T LIMIT THE VM".to_string()));
+    }
+    // The lcov file and the path the program goes by in it
+    let lcov = match (lcov, args.peek().map(String::as_str)) {
+        (None, _) => None,
+        (Some(_), _) if use_vm => {
+            return Err(Errors::ArgumentError("CAN'T COVER THE VM".to_string()))
+        }
+        (Some(_), _) if debug || profile.is_some() => {
+            return Err(Errors::ArgumentError(
+                "CAN'T COVER WHILE DEBUGGING OR PROFILING".to_string(),
+            ))
+        }
+        (Some(_), None | Some("-") | Some("-e")) => {
+            return Err(Errors::ArgumentError("CAN'T COVER WITHOUT A FILE".to_string()))
+        }
+        (Some(lcov), Some(file)) => Some((Coverage::load(&lcov)?, lcov, file.to_string())),
+    };
+
+    let mut stream = read_source(args)?;
 
     let mut lex = lexer::Lexer::new(&mut stream);
 
-    let tokens = lex.lex()?;
+    let (tokens, mut errors) = lex.lex_all();
 
     #[cfg(debug_assertions)]
     {
@@ -36,13 +115,54 @@ fn run_file() -> Result<(), Errors> {
     }
 
     let mut parser = parser::Parser::new(&tokens, &stream);
-    let ast = parser.parse()?;
+    let (ast, parse_errors) = parser.parse_all();
+    errors.extend(parse_errors);
+    if let Some(e) = Errors::combine(errors) {
+        return Err(e);
+    }
 
     #[cfg(debug_assertions)]
     println!("{:#?}\n", ast);
 
-    let mut eval = eval::Eval::new(&ast, &stream);
-    let v = eval.run_prog()?;
+    #[cfg_attr(not(debug_assertions), allow(unused_variables))]
+    let v = if use_vm {
+        let chunk = vm::compile(&ast);
+
+        #[cfg(debug_assertions)]
+        println!("{:#?}\n", chunk);
+
+        let mut vm = vm::Vm::new(&chunk, &ast, &stream);
+        vm.run_prog()?
+    } else {
+        let mut profiler = Profiler::new(ast.len());
+        let mut lines = FileCoverage::new(&ast);
+        let mut eval = eval::Eval::new(&ast, &stream);
+        eval.set_limits(limits);
+        if debug {
+            eval.set_hook(Box::new(Debugger::new()));
+        } else if profile.is_some() {
+            eval.set_hook(Box::new(&mut profiler));
+        } else if lcov.is_some() {
+            eval.set_hook(Box::new(&mut lines));
+        }
+        let v = eval.run_prog();
+        drop(eval);
+
+        if let Some((mut coverage, lcov, file)) = lcov {
+            coverage.add(&file, lines);
+            coverage.save(&lcov)?;
+        }
+
+        // Even if it stopped with an error, that's often when it's wanted
+        profiler.finish();
+        match profile {
+            Some("json") => eprintln!("{}", profiler.to_json()),
+            Some("collapsed") => eprint!("{}", profiler.collapsed()),
+            Some(_) => eprint!("{}", profiler.listing(&stream)),
+            None => (),
+        }
+        v?
+    };
 
     #[cfg(debug_assertions)]
     {
@@ -52,3 +172,117 @@ fn run_file() -> Result<(), Errors> {
 
     Ok(())
 }
+
+// nooblang renumber FILE --insert|--delete LINE [COUNT]
+fn renumber_file() -> Result<(), Errors> {
+    let usage =
+        || Errors::ArgumentError("USAGE: renumber FILE --insert|--delete LINE [COUNT]".to_string());
+    let args: Vec<String> = std::env::args().skip(2).collect();
+    let [file, action, line, count @ ..] = args.as_slice() else {
+        return Err(usage());
+    };
+    let line: usize = line.parse().map_err(|_| usage())?;
+    let count: usize = match count {
+        [] => 1,
+        [count] => count.parse().map_err(|_| usage())?,
+        _ => return Err(usage()),
+    };
+    let edit = match action.as_str() {
+        "--insert" => Edit::Insert(line, count),
+        "--delete" => Edit::Delete(line, count),
+        _ => return Err(usage()),
+    };
+
+    let source = fs::read_to_string(file).map_err(|_| Errors::IOError)?;
+    let (source, notes) = renumber::renumber(&source, edit)?;
+    print!("{}", source);
+    for note in notes {
+        eprintln!("LINE {}: {}", note.line, note.msg);
+    }
+    Ok(())
+}
+
+// nooblang test [--vm] [--bless] [--coverage=LCOV] [PATH...], examples if
+// there's no path
+fn test_files() -> Result<(), Errors> {
+    let mut use_vm = false;
+    let mut bless = false;
+    let mut lcov = None;
+    let mut paths = Vec::new();
+    for arg in std::env::args().skip(2) {
+        if let Some(file) = arg.strip_prefix("--coverage=") {
+            lcov = Some(PathBuf::from(file));
+            continue;
+        }
+        match arg.as_str() {
+            "--vm" => use_vm = true,
+            "--bless" => bless = true,
+            // Used by main when printing the error
+            "--error-format=human" | "--error-format=json" => (),
+            flag if flag.starts_with("--") => {
+                return Err(Errors::ArgumentError(format!("WHAT IS {}", flag)))
+            }
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    if paths.is_empty() {
+        paths.push(PathBuf::from("examples"));
+    }
+    if use_vm && lcov.is_some() {
+        return Err(Errors::ArgumentError("CAN'T COVER THE VM".to_string()));
+    }
+
+    // Adds up with what's already in the file
+    let mut coverage = match &lcov {
+        Some(lcov) => Coverage::load(lcov)?,
+        None => Coverage::new(),
+    };
+    let (mut passed, mut failed) = (0, 0);
+    for path in golden::find(&paths)? {
+        let cover = lcov.as_ref().map(|_| &mut coverage);
+        match golden::check(&path, use_vm, bless, cover)? {
+            Outcome::Pass => {
+                println!("PASS {}", path.display());
+                passed += 1;
+            }
+            Outcome::Fail(diff) => {
+                println!("FAIL {}", path.display());
+                for line in diff {
+                    println!("    {}", line);
+                }
+                failed += 1;
+            }
+            Outcome::Blessed => println!("BLESSED {}", path.display()),
+        }
+    }
+
+    if let Some(lcov) = lcov {
+        coverage.save(&lcov)?;
+    }
+    if bless {
+        return Ok(());
+    }
+    println!("\n{} PASSED, {} FAILED", passed, failed);
+    if failed > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+// nooblang FILE | nooblang - | nooblang -e PROGRAM
+fn read_source(mut args: impl Iterator<Item = String>) -> Result<lexer::Reader, Errors> {
+    match args.next().as_deref() {
+        None => Err(Errors::ArgumentError(
+            "NEED SOURCE FILE AS ARGUMENT".to_string(),
+        )),
+        Some("-") => lexer::Reader::new(stdin().lock()),
+        Some("-e") => match args.next() {
+            Some(program) => Ok(lexer::Reader::from(program.as_str())),
+            None => Err(Errors::ArgumentError("NEED PROGRAM AFTER -e".to_string())),
+        },
+        Some(filename) => {
+            let f = BufReader::new(File::open(filename).map_err(|_| Errors::IOError)?);
+            lexer::Reader::new(f)
+        }
+    }
+}
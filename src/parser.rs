@@ -1,7 +1,10 @@
+use std::collections::HashMap;
+
 use crate::{
     ast::*,
-    errors::Errors,
-    lexer::{Lexer, Pos, Reader},
+    errors::{Errors, Kind},
+    lexer::{Lexer, Pos, Reader, Span},
+    ratio::Ratio,
     token::*,
 };
 
@@ -9,6 +12,10 @@ pub struct Parser<'a> {
     input: &'a Vec<Token>,
     pt: usize,
     reader: &'a Reader,
+    // Name -> line
+    labels: HashMap<String, usize>,
+    // Labels only mean line numbers in 'run'
+    in_run: bool,
 }
 
 impl<'a> Parser<'a> {
@@ -17,13 +24,19 @@ impl<'a> Parser<'a> {
             input,
             pt: 0,
             reader,
+            labels: HashMap::new(),
+            in_run: false,
         }
     }
 
+    pub fn labels(&self) -> &HashMap<String, usize> {
+        &self.labels
+    }
+
     fn read(&mut self) -> &Token {
         let tok = &self.input[self.pt];
         self.pt += 1;
-        return tok;
+        tok
     }
 
     fn peek(&self) -> &Token {
@@ -34,55 +47,172 @@ impl<'a> Parser<'a> {
         self.pt -= 1;
     }
 
+    // From start to the end of the last token read
+    fn since(&self, start: Span) -> Span {
+        Span(start.0, self.input[self.pt - 1].location.1)
+    }
+
     pub fn parse(&mut self) -> Result<Vec<Stmt>, Errors> {
+        let (prog, errors) = self.parse_all();
+        match Errors::combine(errors) {
+            Some(e) => Err(e),
+            None => Ok(prog),
+        }
+    }
+
+    // Keeps going after an error, the line it's on becomes blank
+    pub fn parse_all(&mut self) -> (Vec<Stmt>, Vec<Errors>) {
+        let mut errors = self.find_labels();
         let mut prog = Vec::new();
         loop {
             // Each iteration is a line
+            let labelled = self.peek().token_type == TokenType::Label;
+            if labelled {
+                match self.input[self.pt + 1].token_type {
+                    // Already in self.labels
+                    TokenType::Id(_) => self.pt += 2,
+                    // Already an error
+                    _ => self.skip_line(),
+                }
+            }
+
             match self.peek().token_type {
-                TokenType::Eof => break,
+                TokenType::Eof => {
+                    // Keep the last line if it's only a label
+                    if labelled {
+                        prog.push(Stmt::Blank);
+                    }
+                    break;
+                }
                 TokenType::Lf => {
-                    self.read();
-                    prog.push(Stmt::Blank);
+                    let line = self.read().location.line();
+                    Self::pad(&mut prog, line);
                 }
                 _ => {
-                    prog.push(self.parse_switch()?);
-                    let eol = self.peek();
-                    match eol.token_type {
-                        TokenType::Eof => break,
-                        TokenType::Lf => {
-                            self.read();
+                    let stmt = self.parse_switch().and_then(|stmt| {
+                        match self.peek().token_type {
+                            TokenType::Eof | TokenType::Lf => Ok(stmt),
+                            _ => {
+                                // All of the extra stuff
+                                let start = self.peek().location;
+                                self.skip_line();
+                                let span = self.since(start);
+                                Err(self.parse_error(
+                                    Kind::WhyThisHere,
+                                    "WHY THIS HERE".to_string(),
+                                    span,
+                                ))
+                            }
                         }
-                        _ => {
-                            return Err(self.parse_error("WHY THIS HERE".to_string(), eol.location))
+                    });
+                    match stmt {
+                        Ok(stmt) => prog.push(stmt),
+                        Err(e) => {
+                            errors.push(e);
+                            prog.push(Stmt::Blank);
+                            self.skip_line();
                         }
                     }
+                    let eol = self.read();
+                    let (line, eof) = (eol.location.line(), eol.token_type == TokenType::Eof);
+                    Self::pad(&mut prog, line);
+                    if eof {
+                        break;
+                    }
                 }
             };
         }
-        Ok(prog)
+        (prog, errors)
+    }
+
+    // Lines inside multiline text are blank, so line numbers still match
+    fn pad(prog: &mut Vec<Stmt>, line: usize) {
+        while prog.len() < line {
+            prog.push(Stmt::Blank);
+        }
+    }
+
+    // Up to the end of the line
+    fn skip_line(&mut self) {
+        self.pt = self.pt.min(self.input.len() - 1);
+        while !matches!(self.peek().token_type, TokenType::Lf | TokenType::Eof) {
+            self.pt += 1;
+        }
+    }
+
+    // Labels can be used before the line they're on
+    fn find_labels(&mut self) -> Vec<Errors> {
+        let input = self.input;
+        let mut errors = Vec::new();
+        let mut line_start = true;
+        for (i, tok) in input.iter().enumerate() {
+            if line_start && tok.token_type == TokenType::Label {
+                match input.get(i + 1).map(|t| &t.token_type) {
+                    Some(TokenType::Id(name)) => {
+                        if self.labels.contains_key(name) {
+                            let span = input[i + 1].location;
+                            errors.push(self.parse_error(
+                                Kind::LabelUsedTwice,
+                                format!("LABEL {} USED TWICE", name),
+                                span,
+                            ));
+                        } else {
+                            self.labels.insert(name.to_owned(), tok.location.line());
+                        }
+                    }
+                    Some(TokenType::Be) => errors.extend(self.keyword_now(tok)),
+                    _ => errors.push(self.parse_error(
+                        Kind::NeedLabelName,
+                        "NEED LABEL NAME".to_string(),
+                        tok.location,
+                    )),
+                }
+            }
+            line_start = tok.token_type == TokenType::Lf;
+        }
+        errors
     }
 
     fn parse_switch(&mut self) -> Result<Stmt, Errors> {
+        let start = self.peek().location;
         let stmt = self.parse_stmt()?;
         let TokenType::If = self.peek().token_type else {
             return Ok(stmt);
         };
 
         // Found single if
-        let line = self.read().location.0;
-        Ok(Stmt::Switch(
-            Box::new(stmt),
-            Box::new(self.parse_expr()?),
-            line,
-        ))
+        self.read();
+        let cond = self.parse_expr()?;
+
+        // The rest of the line can be another switch
+        let otherwise = if self.peek().token_type == TokenType::Otherwise {
+            self.read();
+            Some(Box::new(self.parse_switch()?))
+        } else {
+            None
+        };
+        let span = self.since(start);
+        Ok(Stmt::Switch(Box::new(stmt), Box::new(cond), otherwise, span))
     }
 
     fn parse_stmt(&mut self) -> Result<Stmt, Errors> {
+        if self.input.get(self.pt + 1).map(|t| &t.token_type) == Some(&TokenType::Be) {
+            if let Some(e) = self.keyword_now(self.peek()) {
+                return Err(e);
+            }
+        }
         let tok = self.read();
-        let Pos(line, _) = tok.location;
+        let start = tok.location;
         Ok(match tok.token_type {
-            TokenType::Run => self.parse_run()?,
-            TokenType::Write => Stmt::Write(Box::from(self.parse_expr()?), line),
+            TokenType::Run => self.parse_run(start)?,
+            TokenType::Repeat => {
+                let times = self.parse_expr()?;
+                Stmt::Repeat(None, Box::from(times), self.since(start))
+            }
+            TokenType::Write => {
+                let expr = self.parse_expr()?;
+                Stmt::Write(Box::from(expr), self.since(start))
+            }
             TokenType::End => Stmt::End,
             _ => {
                 self.back();
@@ -91,31 +221,47 @@ impl<'a> Parser<'a> {
         })
     }
 
-    fn parse_run(&mut self) -> Result<Stmt, Errors> {
+    // After the 'run', which starts at start
+    fn parse_run(&mut self, start: Span) -> Result<Stmt, Errors> {
+        self.in_run = true;
+        let stmt = self.parse_run_target(start);
+        self.in_run = false;
+
+        if let TokenType::Repeat = self.peek().token_type {
+            self.read();
+            let times = self.parse_expr()?;
+            let span = self.since(start);
+            return Ok(Stmt::Repeat(Some(Box::new(stmt?)), Box::from(times), span));
+        }
+        stmt
+    }
+
+    fn parse_run_target(&mut self, start: Span) -> Result<Stmt, Errors> {
         let from = self.parse_expr()?;
-        let tok = self.peek();
-        let location = tok.location;
-        if let TokenType::To = tok.token_type {
+        if let TokenType::To = self.peek().token_type {
             self.read();
             let to = self.parse_expr()?;
-            Ok(Stmt::RunFrom(Box::from(from), Box::from(to), location.0))
+            Ok(Stmt::RunFrom(Box::from(from), Box::from(to), self.since(start)))
         } else {
-            Ok(Stmt::RunAt(Box::from(from), location.0))
+            Ok(Stmt::RunAt(Box::from(from), self.since(start)))
         }
     }
 
     fn parse_asgn(&mut self) -> Result<Stmt, Errors> {
-        let location = self.peek().location;
+        let start = self.peek().location;
         let left = self.parse_expr()?;
         if TokenType::Be == self.peek().token_type {
             self.read();
             match left {
-                Expr::Id(..) => Ok(Stmt::Asgn(
-                    Box::from(left),
-                    Box::from(self.parse_expr()?),
-                    location.0,
+                Expr::Id(..) => {
+                    let right = self.parse_expr()?;
+                    Ok(Stmt::Asgn(Box::from(left), Box::from(right), self.since(start)))
+                }
+                _ => Err(self.parse_error(
+                    Kind::WhereIdentifier,
+                    "WHERE IDENTIFIER".to_string(),
+                    left.span(),
                 )),
-                _ => Err(self.parse_error("WHERE IDENTIFIER".to_string(), location)),
             }
         } else {
             Ok(Stmt::Expr(Box::from(left)))
@@ -140,38 +286,24 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_or(&mut self) -> Result<Expr, Errors> {
+        let start = self.peek().location;
         let mut ast = self.parse_and()?;
-        loop {
-            let tok = self.read();
-            let location = tok.location;
-            ast = Expr::Binary(
-                match tok.token_type {
-                    TokenType::Or => Operator::Or,
-                    _ => break,
-                },
-                Box::new(ast),
-                Box::from(self.parse_and()?),
-                location,
-            );
+        while let TokenType::Or = self.read().token_type {
+            let right = self.parse_and()?;
+            let span = self.since(start);
+            ast = Expr::Binary(Operator::Or, Box::new(ast), Box::new(right), span);
         }
         self.back();
         Ok(ast)
     }
 
     fn parse_and(&mut self) -> Result<Expr, Errors> {
+        let start = self.peek().location;
         let mut ast = self.parse_not()?;
-        loop {
-            let tok = self.read();
-            let location = tok.location;
-            ast = Expr::Binary(
-                match tok.token_type {
-                    TokenType::And => Operator::And,
-                    _ => break,
-                },
-                Box::new(ast),
-                Box::from(self.parse_not()?),
-                location,
-            );
+        while let TokenType::And = self.read().token_type {
+            let right = self.parse_not()?;
+            let span = self.since(start);
+            ast = Expr::Binary(Operator::And, Box::new(ast), Box::new(right), span);
         }
         self.back();
         Ok(ast)
@@ -179,109 +311,109 @@ impl<'a> Parser<'a> {
 
     fn parse_not(&mut self) -> Result<Expr, Errors> {
         if self.peek().token_type == TokenType::Not {
-            let location = self.read().location;
-            Ok(Expr::Unary(
-                Operator::Not,
-                Box::new(self.parse_is()?),
-                location,
-            ))
+            let start = self.read().location;
+            let operand = self.parse_is()?;
+            Ok(Expr::Unary(Operator::Not, Box::new(operand), self.since(start)))
         } else {
             self.parse_is()
         }
     }
 
     fn parse_is(&mut self) -> Result<Expr, Errors> {
+        let start = self.peek().location;
         let mut ast = self.parse_cmp()?;
         loop {
-            let tok = self.read();
-            let location = tok.location;
-            ast = Expr::Binary(
-                match tok.token_type {
-                    TokenType::Is => Operator::Is,
-                    TokenType::Isnt => Operator::Isnt,
-                    _ => break,
-                },
-                Box::new(ast),
-                Box::from(self.parse_cmp()?),
-                location,
-            );
+            let op = match self.read().token_type {
+                TokenType::Is => Operator::Is,
+                TokenType::Isnt => Operator::Isnt,
+                _ => break,
+            };
+            let right = self.parse_cmp()?;
+            ast = Expr::Binary(op, Box::new(ast), Box::new(right), self.since(start));
         }
         self.back();
         Ok(ast)
     }
 
     fn parse_cmp(&mut self) -> Result<Expr, Errors> {
+        let start = self.peek().location;
+        let mut ast = self.parse_pick()?;
+        loop {
+            let op = match self.read().token_type {
+                TokenType::Below => Operator::Below,
+                TokenType::Above => Operator::Above,
+                TokenType::AtMost => Operator::AtMost,
+                TokenType::AtLeast => Operator::AtLeast,
+                _ => break,
+            };
+            let right = self.parse_pick()?;
+            ast = Expr::Binary(op, Box::new(ast), Box::new(right), self.since(start));
+        }
+        self.back();
+        Ok(ast)
+    }
+
+    fn parse_pick(&mut self) -> Result<Expr, Errors> {
+        let start = self.peek().location;
         let mut ast = self.parse_arith()?;
         loop {
-            let tok = self.read();
-            let location = tok.location;
-            ast = Expr::Binary(
-                match tok.token_type {
-                    TokenType::Below => Operator::Below,
-                    TokenType::Above => Operator::Above,
-                    TokenType::AtMost => Operator::AtMost,
-                    TokenType::AtLeast => Operator::AtLeast,
-                    _ => break,
-                },
-                Box::new(ast),
-                Box::from(self.parse_arith()?),
-                location,
-            );
+            let op = match self.read().token_type {
+                TokenType::Min => Operator::Min,
+                TokenType::Max => Operator::Max,
+                _ => break,
+            };
+            let right = self.parse_arith()?;
+            ast = Expr::Binary(op, Box::new(ast), Box::new(right), self.since(start));
         }
         self.back();
         Ok(ast)
     }
 
     fn parse_arith(&mut self) -> Result<Expr, Errors> {
+        let start = self.peek().location;
         let mut ast = self.parse_term()?;
         loop {
-            let tok = self.read();
-            let location = tok.location;
-            ast = Expr::Binary(
-                match tok.token_type {
-                    TokenType::Plus => Operator::Plus,
-                    TokenType::Minus => Operator::Minus,
-                    _ => break,
-                },
-                Box::new(ast),
-                Box::from(self.parse_term()?),
-                location,
-            );
+            let op = match self.read().token_type {
+                TokenType::Plus => Operator::Plus,
+                TokenType::Minus => Operator::Minus,
+                _ => break,
+            };
+            let right = self.parse_term()?;
+            ast = Expr::Binary(op, Box::new(ast), Box::new(right), self.since(start));
         }
         self.back();
         Ok(ast)
     }
 
     fn parse_term(&mut self) -> Result<Expr, Errors> {
+        let start = self.peek().location;
         let mut ast = self.parse_exp()?;
         loop {
-            let tok = self.read();
-            let location = tok.location;
-            ast = Expr::Binary(
-                match tok.token_type {
-                    TokenType::Times => Operator::Times,
-                    TokenType::Over => Operator::Over,
-                    TokenType::Mod => Operator::Mod,
-                    _ => break,
-                },
-                Box::new(ast),
-                Box::from(self.parse_exp()?),
-                location,
-            );
+            let op = match self.read().token_type {
+                TokenType::Times => Operator::Times,
+                TokenType::Over => Operator::Over,
+                TokenType::Mod => Operator::Mod,
+                TokenType::Div => Operator::Div,
+                _ => break,
+            };
+            let right = self.parse_exp()?;
+            ast = Expr::Binary(op, Box::new(ast), Box::new(right), self.since(start));
         }
         self.back();
         Ok(ast)
     }
 
     fn parse_exp(&mut self) -> Result<Expr, Errors> {
+        let start = self.peek().location;
         let left = self.parse_conv()?;
         if TokenType::ToThe == self.peek().token_type {
-            let location = self.read().location;
+            self.read();
+            let right = self.parse_conv()?;
             Ok(Expr::Binary(
                 Operator::ToThe,
                 Box::from(left),
-                Box::from(self.parse_conv()?),
-                location,
+                Box::from(right),
+                self.since(start),
             ))
         } else {
             Ok(left)
@@ -290,53 +422,117 @@ impl<'a> Parser<'a> {
 
     fn parse_conv(&mut self) -> Result<Expr, Errors> {
         let tok = self.read();
-        let location = tok.location;
-        Ok(Expr::Unary(
-            match tok.token_type {
-                TokenType::Num => Operator::Num,
-                TokenType::Text => Operator::Text,
-                TokenType::Choice => Operator::Choice,
-                _ => {
-                    self.back();
-                    return self.parse_neg();
-                }
-            },
-            Box::from(self.parse_neg()?),
-            location,
-        ))
+        let start = tok.location;
+        let op = match tok.token_type {
+            TokenType::Num => Operator::Num,
+            TokenType::Text => Operator::Text,
+            TokenType::Choice => Operator::Choice,
+            TokenType::Floor => Operator::Floor,
+            TokenType::Ceil => Operator::Ceil,
+            TokenType::Round => Operator::Round,
+            TokenType::Abs => Operator::Abs,
+            TokenType::Sqrt => Operator::Sqrt,
+            TokenType::Upper => Operator::Upper,
+            TokenType::Lower => Operator::Lower,
+            TokenType::Character => Operator::Character,
+            TokenType::Length | TokenType::Code => {
+                self.back();
+                return self.parse_of();
+            }
+            TokenType::Letter | TokenType::Part | TokenType::Find => {
+                self.back();
+                return self.parse_text();
+            }
+            _ => {
+                self.back();
+                return self.parse_neg();
+            }
+        };
+        self.not_a_name()?;
+        let operand = self.parse_conv()?;
+        Ok(Expr::Unary(op, Box::from(operand), self.since(start)))
+    }
+
+    // 'length of X' and 'code of X'
+    fn parse_of(&mut self) -> Result<Expr, Errors> {
+        let tok = self.read();
+        let start = tok.location;
+        let op = match tok.token_type {
+            TokenType::Length => Operator::Length,
+            _ => Operator::Code,
+        };
+        self.not_a_name()?;
+        self.expect(TokenType::Of, Kind::NeedOf, "NEED OF", start)?;
+        let operand = self.parse_conv()?;
+        Ok(Expr::Unary(op, Box::from(operand), self.since(start)))
+    }
+
+    // 'letter N of X', 'part A to B of X' and 'find X in Y'
+    fn parse_text(&mut self) -> Result<Expr, Errors> {
+        let tok = self.read();
+        let (start, op) = (tok.location, tok.token_type.clone());
+        self.not_a_name()?;
+        let first = Box::from(self.parse_expr()?);
+        match op {
+            TokenType::Part => {
+                self.expect(TokenType::To, Kind::NeedTo, "NEED TO", start)?;
+                let last = Box::from(self.parse_expr()?);
+                self.expect(TokenType::Of, Kind::NeedOf, "NEED OF", start)?;
+                let text = Box::from(self.parse_conv()?);
+                Ok(Expr::Ternary(Operator::Part, first, last, text, self.since(start)))
+            }
+            TokenType::Find => {
+                self.expect(TokenType::In, Kind::NeedIn, "NEED IN", start)?;
+                let text = Box::from(self.parse_conv()?);
+                Ok(Expr::Binary(Operator::Find, first, text, self.since(start)))
+            }
+            _ => {
+                self.expect(TokenType::Of, Kind::NeedOf, "NEED OF", start)?;
+                let text = Box::from(self.parse_conv()?);
+                Ok(Expr::Binary(Operator::Letter, first, text, self.since(start)))
+            }
+        }
     }
 
     fn parse_neg(&mut self) -> Result<Expr, Errors> {
         if self.peek().token_type == TokenType::Neg {
-            let location = self.read().location;
-            Ok(Expr::Unary(
-                Operator::Neg,
-                Box::from(self.parse_decimal()?),
-                location,
-            ))
+            let start = self.read().location;
+            let operand = self.parse_decimal()?;
+            Ok(Expr::Unary(Operator::Neg, Box::from(operand), self.since(start)))
         } else {
             self.parse_decimal()
         }
     }
 
     fn parse_decimal(&mut self) -> Result<Expr, Errors> {
+        let start = self.peek().location;
         let left = self.parse_atom()?;
         if self.peek().token_type == TokenType::Dot {
-            let location = self.read().location;
+            self.read();
             let right = self.parse_atom()?;
+            let location = self.since(start);
             match (left, right) {
-                (Expr::Literal(Literal::Integer(a), _), Expr::Literal(Literal::Integer(b), _)) => {
-                    let pow = if b == 0 {
-                        1.0
-                    } else {
-                        (b as f64).log10().ceil()
+                (
+                    Expr::Literal(Literal::Integer(a), _),
+                    Expr::Literal(Literal::Integer(b), Span(Pos(_, from), Pos(_, to))),
+                ) => {
+                    // Digits as written, so 1 dot 05 is 1.05
+                    let digits = to - from;
+                    let n = match Ratio::decimal(a, b, digits) {
+                        Some(n) => Literal::Decimal(n),
+                        None => Literal::Float(
+                            format!("{}.{:0>2$}", a, b, digits)
+                                .parse()
+                                .unwrap_or(f64::NAN),
+                        ),
                     };
-                    Ok(Expr::Literal(
-                        Literal::Decimal(a as f64 + b as f64 / 10_f64.powf(pow)),
-                        location,
-                    ))
+                    Ok(Expr::Literal(n, location))
                 }
-                _ => Err(self.parse_error("INVALID DECIMAL".to_string(), location)),
+                _ => Err(self.parse_error(
+                    Kind::InvalidDecimal,
+                    "INVALID DECIMAL".to_string(),
+                    location,
+                )),
             }
         } else {
             Ok(left)
@@ -350,14 +546,21 @@ impl<'a> Parser<'a> {
             TokenType::Number(_) | TokenType::String(_) | TokenType::Yes | TokenType::No => {
                 self.parse_literal()
             }
+            #[cfg(feature = "bigint")]
+            TokenType::BigNumber(_) => self.parse_literal(),
             TokenType::Open => self.parse_paren(),
-            _ => return Err(self.parse_error("NEED VALUE".to_string(), self.peek().location)),
+            _ => Err(self
+                .keyword_now(self.peek())
+                .unwrap_or_else(|| {
+                    let location = self.peek().location;
+                    self.parse_error(Kind::NeedValue, "NEED VALUE".to_string(), location)
+                })),
         }
     }
 
     fn parse_read(&mut self) -> Expr {
         let location = self.read().location;
-        return Expr::Nullary(Operator::Read, location);
+        Expr::Nullary(Operator::Read, location)
     }
 
     fn parse_literal(&mut self) -> Result<Expr, Errors> {
@@ -366,10 +569,18 @@ impl<'a> Parser<'a> {
         Ok(Expr::Literal(
             match tok.token_type {
                 TokenType::Number(v) => Literal::Integer(v),
+                #[cfg(feature = "bigint")]
+                TokenType::BigNumber(ref v) => Literal::Big(v.clone()),
                 TokenType::String(ref v) => Literal::Text(v.to_owned()),
                 TokenType::Yes => Literal::Choice(true),
                 TokenType::No => Literal::Choice(false),
-                _ => return Err(self.parse_error("EXPECTED LITERAL".to_string(), location)),
+                _ => {
+                    return Err(self.parse_error(
+                        Kind::ExpectedLiteral,
+                        "EXPECTED LITERAL".to_string(),
+                        location,
+                    ))
+                }
             },
             tok.location,
         ))
@@ -379,28 +590,101 @@ impl<'a> Parser<'a> {
         let tok = self.read();
         let location = tok.location;
         if let TokenType::Id(ref name) = tok.token_type {
-            Ok(Expr::Id(name.to_owned(), tok.location))
+            let name = name.to_owned();
+            if let Some(line) = self.labels.get(&name).filter(|_| self.in_run) {
+                return Ok(Expr::Literal(Literal::Label(name, *line), location));
+            }
+            Ok(Expr::Id(name, location))
         } else {
-            Err(self.parse_error("EXPECTED NAME".to_string(), location))
+            Err(self.parse_error(Kind::ExpectedName, "EXPECTED NAME".to_string(), location))
         }
     }
 
     fn parse_paren(&mut self) -> Result<Expr, Errors> {
-        self.read();
+        let start = self.read().location;
         let ast = self.parse_expr()?;
         if self.peek().token_type != TokenType::Close {
-            return Err(self.parse_error("NEED CLOSE".to_string(), self.peek().location));
+            // Everything that's still open
+            return Err(self.parse_error(
+                Kind::NeedClose,
+                "NEED CLOSE".to_string(),
+                self.since(start),
+            ));
+        }
+        self.read();
+        Ok(ast)
+    }
+
+    // After a word like 'floor', nothing it could go with means it was meant
+    // as a name from before it was a keyword
+    fn not_a_name(&self) -> Result<(), Errors> {
+        #[cfg(feature = "bigint")]
+        let big = matches!(self.peek().token_type, TokenType::BigNumber(_));
+        #[cfg(not(feature = "bigint"))]
+        let big = false;
+        let starts_value = big
+            || matches!(
+                self.peek().token_type,
+                TokenType::Read
+                    | TokenType::Id(_)
+                    | TokenType::Number(_)
+                    | TokenType::String(_)
+                    | TokenType::Yes
+                    | TokenType::No
+                    | TokenType::Open
+                    | TokenType::Neg
+                    | TokenType::Of
+                    // Words that go in front of a value
+                    | TokenType::Num
+                    | TokenType::Text
+                    | TokenType::Choice
+                    | TokenType::Floor
+                    | TokenType::Ceil
+                    | TokenType::Round
+                    | TokenType::Abs
+                    | TokenType::Sqrt
+                    | TokenType::Upper
+                    | TokenType::Lower
+                    | TokenType::Character
+                    | TokenType::Length
+                    | TokenType::Code
+                    | TokenType::Letter
+                    | TokenType::Part
+                    | TokenType::Find
+            );
+        match self.keyword_now(&self.input[self.pt - 1]) {
+            Some(e) if !starts_value => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    fn keyword_now(&self, tok: &Token) -> Option<Errors> {
+        let word = tok.token_type.keyword().filter(|w| NEWER_KEYWORDS.contains(w))?;
+        let word = word.to_uppercase();
+        Some(self.parse_error(Kind::KeywordNow, format!("{} IS A KEYWORD NOW", word), tok.location))
+    }
+
+    // Reads the word that has to come next, the error covers from start
+    fn expect(
+        &mut self,
+        token_type: TokenType,
+        kind: Kind,
+        msg: &str,
+        start: Span,
+    ) -> Result<(), Errors> {
+        if self.peek().token_type != token_type {
+            return Err(self.parse_error(kind, msg.to_string(), self.since(start)));
         }
         self.read();
-        return Ok(ast);
+        Ok(())
     }
 
-    fn parse_error(&self, msg: String, location: Pos) -> Errors {
-        let Pos(line, _) = location;
+    fn parse_error(&self, kind: Kind, msg: String, location: Span) -> Errors {
         Errors::SyntaxError(
             msg,
             location,
-            Lexer::to_string_lossy(self.reader.get_line(line)),
+            Lexer::to_string_lossy(self.reader.get_line(location.line())),
+            kind,
         )
     }
 }
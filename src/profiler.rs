@@ -0,0 +1,182 @@
+use std::{collections::BTreeMap, fmt::Write, time::Instant};
+
+use crate::{
+    errors::Errors,
+    eval::{Eval, Hook},
+    json::Json,
+    lexer::{Lexer, Reader, Span},
+};
+
+// Counts what each line does while a program runs, for --profile
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LineStats {
+    // Times its statement ran
+    pub hits: u64,
+    // From when it started until the next line did, so the lines a 'run'
+    // goes through have their own time
+    pub nanos: u128,
+    // How the 'if's on it went
+    pub yes: u64,
+    pub no: u64,
+}
+
+// 'run's from the outside in, as (first line, last line), then the line
+type Stack = (Vec<(usize, usize)>, usize);
+
+// Loops made of 'run's keep going deeper, so only the innermost 'run's go
+// in a stack, with (0, 0) for the ones left out
+const MAX_STACK: usize = 64;
+
+pub struct Profiler {
+    // Line n is lines[n - 1]
+    lines: Vec<LineStats>,
+    // Lines a 'run' went through -> times
+    runs: BTreeMap<(usize, usize), u64>,
+    // For flamegraphs, nanoseconds in each line with the 'run's around it
+    stacks: BTreeMap<Stack, u128>,
+    // The line running now and since when
+    current: Option<(Stack, Instant)>,
+}
+
+impl Profiler {
+    pub fn new(lines: usize) -> Self {
+        Self {
+            lines: vec![LineStats::default(); lines],
+            runs: BTreeMap::new(),
+            stacks: BTreeMap::new(),
+            current: None,
+        }
+    }
+
+    // Stops the clock on the last line, once the program is done
+    pub fn finish(&mut self) {
+        if let Some((stack, started)) = self.current.take() {
+            let nanos = started.elapsed().as_nanos();
+            self.lines[stack.1 - 1].nanos += nanos;
+            *self.stacks.entry(stack).or_default() += nanos;
+        }
+    }
+
+    pub fn lines(&self) -> &[LineStats] {
+        &self.lines
+    }
+
+    pub fn runs(&self) -> &BTreeMap<(usize, usize), u64> {
+        &self.runs
+    }
+
+    // The source with the numbers next to each line, then the 'run's
+    pub fn listing(&self, reader: &Reader) -> String {
+        let mut out = String::new();
+        let total: u128 = self.lines.iter().map(|stats| stats.nanos).sum();
+        let _ = writeln!(out, "{:>5} {:>9} {:>11}  SOURCE", "LINE", "HITS", "TIME");
+        for (i, stats) in self.lines.iter().enumerate() {
+            let text = Lexer::to_string_lossy(reader.get_line(i + 1));
+            let text = text.trim_end();
+            let time = match stats.hits {
+                0 => String::new(),
+                _ => millis(stats.nanos),
+            };
+            let _ = write!(out, "{:>5} {:>9} {:>11}  {}", i + 1, stats.hits, time, text);
+            if stats.yes + stats.no > 0 {
+                let _ = write!(out, "    <- IF: {} YES, {} NO", stats.yes, stats.no);
+            }
+            out.push('\n');
+        }
+        let _ = writeln!(out, "TOTAL {:>21}", millis(total));
+
+        if !self.runs.is_empty() {
+            let _ = writeln!(out, "\nRUNS");
+            for ((from, to), times) in &self.runs {
+                let _ = writeln!(out, "{:>5} TO {:<5} {:>9} TIMES", from, to, times);
+            }
+        }
+        out
+    }
+
+    pub fn to_json(&self) -> Json {
+        let lines = self
+            .lines
+            .iter()
+            .enumerate()
+            .map(|(i, stats)| {
+                Json::object(vec![
+                    ("line", (i + 1).into()),
+                    ("hits", (stats.hits as usize).into()),
+                    ("nanos", Json::Number(stats.nanos as f64)),
+                    ("yes", (stats.yes as usize).into()),
+                    ("no", (stats.no as usize).into()),
+                ])
+            })
+            .collect::<Vec<_>>();
+        let runs = self
+            .runs
+            .iter()
+            .map(|((from, to), times)| {
+                Json::object(vec![
+                    ("from", (*from).into()),
+                    ("to", (*to).into()),
+                    ("times", (*times as usize).into()),
+                ])
+            })
+            .collect::<Vec<_>>();
+        Json::object(vec![("lines", lines.into()), ("runs", runs.into())])
+    }
+
+    // One 'lines 1-11;lines 4-7;line 5 NANOS' per line, what flamegraph.pl
+    // and friends read
+    pub fn collapsed(&self) -> String {
+        let mut out = String::new();
+        for ((runs, line), nanos) in &self.stacks {
+            for (from, to) in runs {
+                match from {
+                    0 => out.push_str("...;"),
+                    _ => {
+                        let _ = write!(out, "lines {}-{};", from, to);
+                    }
+                }
+            }
+            let _ = writeln!(out, "line {} {}", line, nanos);
+        }
+        out
+    }
+}
+
+fn millis(nanos: u128) -> String {
+    format!("{:.3}ms", nanos as f64 / 1e6)
+}
+
+impl Hook for Profiler {
+    fn line(&mut self, eval: &mut Eval, line: usize) -> Result<(), Errors> {
+        self.finish();
+        self.lines[line - 1].hits += 1;
+        // Finished 'run's are gone, so the top of the stack is what's running
+        let mut runs: Vec<_> = eval
+            .call_stack()
+            .iter()
+            .rev()
+            .map(|frame| (frame.start, frame.to))
+            .take(MAX_STACK + 1)
+            .collect();
+        if runs.len() > MAX_STACK {
+            runs[MAX_STACK] = (0, 0);
+        }
+        runs.reverse();
+        // Last, so the time spent here doesn't count
+        self.current = Some(((runs, line), Instant::now()));
+        Ok(())
+    }
+
+    fn switch(&mut self, cond: Span, yes: bool) {
+        let stats = &mut self.lines[cond.line() - 1];
+        match yes {
+            true => stats.yes += 1,
+            false => stats.no += 1,
+        }
+    }
+
+    fn run(&mut self, _line: usize, from: usize, to: usize) {
+        *self.runs.entry((from, to)).or_default() += 1;
+    }
+}
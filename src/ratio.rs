@@ -0,0 +1,215 @@
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+};
+
+// Exact fractions like 1/3 or 1.05, so 'over' and 'dot' don't lose anything.
+// Anything that doesn't fit in i128 comes out as None, for a decimal instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ratio {
+    num: i128,
+    // Always above 0, nothing in common with num
+    den: i128,
+}
+
+impl Ratio {
+    pub fn new(num: i128, den: i128) -> Option<Self> {
+        if den == 0 {
+            return None;
+        }
+        let g = gcd(num, den);
+        let (mut num, mut den) = (num / g, den / g);
+        if den < 0 {
+            num = num.checked_neg()?;
+            den = den.checked_neg()?;
+        }
+        Some(Self { num, den })
+    }
+
+    pub fn whole(n: i128) -> Self {
+        Self { num: n, den: 1 }
+    }
+
+    pub fn num(&self) -> i128 {
+        self.num
+    }
+
+    pub fn den(&self) -> i128 {
+        self.den
+    }
+
+    pub fn is_whole(&self) -> bool {
+        self.den == 1
+    }
+
+    // "1.05", "-3", "1/3" or "2.5/7"
+    pub fn parse(text: &str) -> Option<Self> {
+        if let Some((num, den)) = text.split_once('/') {
+            return Self::parse(num)?.checked_div(&Self::parse(den)?);
+        }
+        let (neg, digits) = match text.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, text),
+        };
+        let (whole, frac) = digits.split_once('.').unwrap_or((digits, ""));
+        if whole.is_empty() || !(whole.bytes().chain(frac.bytes())).all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let n = Self::decimal(whole.parse().ok()?, frac.parse().unwrap_or(0), frac.len())?;
+        if neg {
+            n.checked_neg()
+        } else {
+            Some(n)
+        }
+    }
+
+    // whole.frac, where frac was written with this many digits
+    pub fn decimal(whole: u128, frac: u128, digits: usize) -> Option<Self> {
+        let scale = 10i128.checked_pow(u32::try_from(digits).ok()?)?;
+        let num = i128::try_from(whole)
+            .ok()?
+            .checked_mul(scale)?
+            .checked_add(i128::try_from(frac).ok()?)?;
+        Self::new(num, scale)
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        let g = gcd(self.den, other.den);
+        let den = (self.den / g).checked_mul(other.den)?;
+        let num = self
+            .num
+            .checked_mul(other.den / g)?
+            .checked_add(other.num.checked_mul(self.den / g)?)?;
+        Self::new(num, den)
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        self.checked_add(&other.checked_neg()?)
+    }
+
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        // Cancel first so it overflows less often
+        let a = gcd(self.num, other.den);
+        let b = gcd(other.num, self.den);
+        let num = (self.num / a).checked_mul(other.num / b)?;
+        let den = (self.den / b).checked_mul(other.den / a)?;
+        Self::new(num, den)
+    }
+
+    pub fn checked_div(&self, other: &Self) -> Option<Self> {
+        self.checked_mul(&Self::new(other.den, other.num)?)
+    }
+
+    // What's left after taking out whole times of other, same sign as self
+    // like % on whole numbers
+    pub fn checked_rem(&self, other: &Self) -> Option<Self> {
+        let times = self.checked_div(other)?;
+        let times = Self::whole(times.num / times.den);
+        self.checked_sub(&times.checked_mul(other)?)
+    }
+
+    pub fn checked_neg(&self) -> Option<Self> {
+        Some(Self {
+            num: self.num.checked_neg()?,
+            den: self.den,
+        })
+    }
+
+    pub fn checked_pow(&self, exp: i128) -> Option<Self> {
+        let base = if exp < 0 {
+            Self::new(self.den, self.num)?
+        } else {
+            *self
+        };
+        let exp = u32::try_from(exp.unsigned_abs()).ok()?;
+        Some(Self {
+            num: base.num.checked_pow(exp)?,
+            den: base.den.checked_pow(exp)?,
+        })
+    }
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    // Only i128::MIN and 0 would make this too big
+    i128::try_from(a).unwrap_or(1).max(1)
+}
+
+impl Ord for Ratio {
+    // Whole parts first, then the flipped leftovers, so it never overflows
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b) = (self.num.div_euclid(self.den), other.num.div_euclid(other.den));
+        if a != b {
+            return a.cmp(&b);
+        }
+        let (a, b) = (self.num.rem_euclid(self.den), other.num.rem_euclid(other.den));
+        match (a, b) {
+            (0, 0) => Ordering::Equal,
+            (0, _) => Ordering::Less,
+            (_, 0) => Ordering::Greater,
+            // a/den vs b/den': the bigger one has the smaller flip
+            _ => Self {
+                num: other.den,
+                den: b,
+            }
+            .cmp(&Self {
+                num: self.den,
+                den: a,
+            }),
+        }
+    }
+}
+
+impl PartialOrd for Ratio {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Every digit if it ends (1.05), as a fraction if it doesn't (1/3)
+impl Display for Ratio {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut den = self.den;
+        while den % 2 == 0 {
+            den /= 2;
+        }
+        while den % 5 == 0 {
+            den /= 5;
+        }
+        if den != 1 {
+            return write!(f, "{}/{}", self.num, self.den);
+        }
+
+        if self.num < 0 {
+            write!(f, "-")?;
+        }
+        let (num, den) = (self.num.unsigned_abs(), self.den.unsigned_abs());
+        write!(f, "{}", num / den)?;
+        let mut rem = num % den;
+        if rem != 0 {
+            write!(f, ".")?;
+        }
+        while rem != 0 {
+            // rem * 10 / den and rem * 10 % den, without going past u128
+            let (mut digit, mut next) = (0, 0);
+            for _ in 0..10 {
+                if next >= den - rem {
+                    next -= den - rem;
+                    digit += 1;
+                } else {
+                    next += rem;
+                }
+            }
+            write!(f, "{}", digit)?;
+            rem = next;
+        }
+        Ok(())
+    }
+}
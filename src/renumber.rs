@@ -0,0 +1,180 @@
+use crate::{
+    ast::*,
+    errors::Errors,
+    lexer::{Pos, Reader, Span},
+};
+
+// Lines to add (blank) before, or remove starting at, a line
+#[derive(Debug, Clone, Copy)]
+pub enum Edit {
+    Insert(usize, usize),
+    Delete(usize, usize),
+}
+
+// Something a human has to look at, in the new line numbers
+#[derive(Debug)]
+pub struct Note {
+    pub line: usize,
+    pub msg: String,
+}
+
+#[derive(Clone, Copy)]
+enum Target {
+    From,
+    To,
+    At,
+}
+
+impl Edit {
+    // Where an old line ends up, or None if it's gone
+    fn line(&self, line: usize) -> Option<usize> {
+        match *self {
+            Edit::Insert(at, count) if line >= at => Some(line + count),
+            Edit::Delete(at, count) if line >= at + count => Some(line - count),
+            Edit::Delete(at, _) if line >= at => None,
+            _ => Some(line),
+        }
+    }
+
+    // Where a 'run' target should point after the edit
+    fn target(&self, line: usize, target: Target) -> usize {
+        let (Edit::Insert(at, _) | Edit::Delete(at, _)) = *self;
+        match (self.line(line), target) {
+            (Some(line), _) => line,
+            // Deleted, so shrink the range to what's left
+            (None, Target::To) => at - 1,
+            (None, _) => at,
+        }
+    }
+}
+
+// Apply the edit to the source and fix up literal line numbers in 'run's
+pub fn renumber(source: &str, edit: Edit) -> Result<(String, Vec<Note>), Errors> {
+    let mut reader = Reader::from(source);
+    let prog = crate::parse(&mut reader)?;
+
+    let valid = match edit {
+        Edit::Insert(at, count) => at >= 1 && count >= 1 && at <= prog.len() + 1,
+        Edit::Delete(at, count) => at >= 1 && count >= 1 && at + count - 1 <= prog.len(),
+    };
+    if !valid {
+        let (Edit::Insert(at, _) | Edit::Delete(at, _)) = edit;
+        return Err(Errors::ArgumentError(format!("CAN'T CHANGE LINE {}", at)));
+    }
+
+    let mut lines: Vec<String> = source.split('\n').map(str::to_string).collect();
+    let mut notes = Vec::new();
+
+    for (i, stmt) in prog.iter().enumerate() {
+        let Some(new_line) = edit.line(i + 1) else {
+            continue;
+        };
+
+        let mut edits = Vec::new();
+        for (expr, target) in targets(stmt) {
+            match expr {
+                Expr::Literal(Literal::Integer(n), Span(Pos(_, start), Pos(_, end))) => {
+                    // Past the end it's no line at all, and might not fit a usize
+                    let Some(old) = usize::try_from(*n).ok().filter(|old| *old <= prog.len())
+                    else {
+                        notes.push(Note {
+                            line: new_line,
+                            msg: format!(
+                                "'run' POINTS PAST THE LAST LINE AT {}, CHECK IT BY HAND",
+                                n
+                            ),
+                        });
+                        continue;
+                    };
+                    let new = edit.target(old, target);
+                    if edit.line(old).is_none() {
+                        notes.push(Note {
+                            line: new_line,
+                            msg: format!("'run' POINTED AT DELETED LINE {}", old),
+                        });
+                    }
+                    if new != old {
+                        edits.push((*start, *end, new));
+                    }
+                }
+                // Moves along with its line
+                Expr::Literal(Literal::Label(..), _) => (),
+                _ => {
+                    let mut numbers = Vec::new();
+                    literals(expr, &mut numbers);
+                    let msg = if numbers.is_empty() {
+                        "COMPUTED 'run' TARGET, CHECK IT BY HAND".to_string()
+                    } else {
+                        format!(
+                            "COMPUTED 'run' TARGET, CHECK {} BY HAND",
+                            numbers.join(", ")
+                        )
+                    };
+                    notes.push(Note {
+                        line: new_line,
+                        msg,
+                    })
+                }
+            }
+        }
+
+        // Right to left so earlier columns stay valid
+        edits.sort_by_key(|edit| std::cmp::Reverse(edit.0));
+        let text = &mut lines[i];
+        for (start, end, new) in edits {
+            let range = byte(text, start)..byte(text, end);
+            text.replace_range(range, &new.to_string());
+        }
+    }
+
+    match edit {
+        Edit::Insert(at, count) => {
+            let at = (at - 1).min(lines.len());
+            lines.splice(at..at, std::iter::repeat_n(String::new(), count));
+        }
+        Edit::Delete(at, count) => {
+            lines.drain(at - 1..at - 1 + count);
+        }
+    }
+
+    Ok((lines.join("\n"), notes))
+}
+
+// Character column -> byte index
+fn byte(text: &str, col: usize) -> usize {
+    text.char_indices()
+        .nth(col - 1)
+        .map_or(text.len(), |(i, _)| i)
+}
+
+// Every line number expression of the 'run's in a statement
+fn targets(stmt: &Stmt) -> Vec<(&Expr, Target)> {
+    match stmt {
+        Stmt::RunFrom(from, to, _) => {
+            vec![(from.as_ref(), Target::From), (to.as_ref(), Target::To)]
+        }
+        Stmt::RunAt(at, _) => vec![(at.as_ref(), Target::At)],
+        Stmt::Repeat(Some(run), ..) => targets(run),
+        Stmt::Switch(stmt, _, otherwise, _) => {
+            let mut all = targets(stmt);
+            if let Some(otherwise) = otherwise {
+                all.extend(targets(otherwise));
+            }
+            all
+        }
+        _ => Vec::new(),
+    }
+}
+
+// Numbers in a computed target that might be line offsets
+fn literals(expr: &Expr, numbers: &mut Vec<String>) {
+    match expr {
+        Expr::Binary(_, left, right, _) => {
+            literals(left, numbers);
+            literals(right, numbers);
+        }
+        Expr::Unary(_, operand, _) => literals(operand, numbers),
+        Expr::Literal(Literal::Integer(n), _) => numbers.push(n.to_string()),
+        _ => (),
+    }
+}
@@ -0,0 +1,192 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{stderr, stdin, stdout, BufRead, Write},
+};
+
+use crate::{
+    ast::Stmt,
+    errors::Errors,
+    eval::{Eval, Io, Name, Stdio, Value},
+    lexer::Reader,
+};
+
+const HELP: &str = "\
+:list         show the program so far
+:vars         show all variables
+:load FILE    replace the program with FILE and run it
+:reset        forget the program and all variables
+:help         show this
+:quit         bye";
+
+// Every entered line becomes the next line of one growing program,
+// so 'run 3 to 5' works on whatever was typed as lines 3 to 5
+pub struct Repl<'a> {
+    lines: Vec<String>,
+    context: HashMap<Name, Value>,
+    // For the program's 'write' and 'read', stdio if not set
+    io: Box<dyn Io + 'a>,
+}
+
+impl<'a> Default for Repl<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Repl<'a> {
+    pub fn new() -> Self {
+        Self {
+            lines: Vec::new(),
+            context: HashMap::new(),
+            io: Box::new(Stdio),
+        }
+    }
+
+    pub fn set_io(&mut self, io: Box<dyn Io + 'a>) {
+        self.io = io;
+    }
+
+    pub fn run(&mut self) -> Result<(), Errors> {
+        self.serve(stdin().lock(), stdout(), stderr())
+    }
+
+    // Lines and commands from the input until it ends or ':quit', values and
+    // command answers to the output and what went wrong to errors
+    pub fn serve(
+        &mut self,
+        mut input: impl BufRead,
+        mut output: impl Write,
+        mut errors: impl Write,
+    ) -> Result<(), Errors> {
+        writeln!(output, "NOOBLANG REPL, :help FOR HELP").map_err(|_| Errors::IOError)?;
+        loop {
+            write!(output, "{}> ", self.lines.len() + 1).map_err(|_| Errors::IOError)?;
+            output.flush().map_err(|_| Errors::IOError)?;
+
+            let mut line = String::new();
+            if input.read_line(&mut line).map_err(|_| Errors::IOError)? == 0 {
+                writeln!(output).map_err(|_| Errors::IOError)?;
+                return Ok(());
+            }
+
+            let line = line.trim_end();
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            if let Some(cmd) = line.trim().strip_prefix(':') {
+                if !self.meta(cmd, &mut output, &mut errors)? {
+                    return Ok(());
+                }
+                continue;
+            }
+
+            let result = self.eval_line(line);
+            Self::show(result, &mut output, &mut errors)?;
+        }
+    }
+
+    // The value, unless there isn't one, or the error
+    fn show(
+        result: Result<Value, Errors>,
+        output: &mut impl Write,
+        errors: &mut impl Write,
+    ) -> Result<(), Errors> {
+        match result {
+            Ok(Value::Nothing) => Ok(()),
+            Ok(v) => writeln!(output, "{}", Eval::display(&v)),
+            Err(e) => writeln!(errors, "{}", e),
+        }
+        .map_err(|_| Errors::IOError)
+    }
+
+    // Add a line to the program and run just that line
+    pub fn eval_line(&mut self, line: &str) -> Result<Value, Errors> {
+        self.lines.push(line.to_string());
+        let n = self.lines.len();
+        let mut reader = Reader::from(self.lines.join("\n").as_str());
+        let ast = match crate::parse(&mut reader) {
+            Ok(ast) => ast,
+            Err(e) => {
+                // Typos don't make it into the program
+                self.lines.pop();
+                return Err(e);
+            }
+        };
+        self.run_range(&ast, &reader, n, n)
+    }
+
+    // Replace the whole program and run it from the top
+    pub fn load(&mut self, source: &str) -> Result<Value, Errors> {
+        let mut reader = Reader::from(source);
+        let ast = crate::parse(&mut reader)?;
+        self.lines = source.lines().map(str::to_string).collect();
+        self.context.clear();
+        self.run_range(&ast, &reader, 1, ast.len())
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    fn run_range(
+        &mut self,
+        ast: &Vec<Stmt>,
+        reader: &Reader,
+        from: usize,
+        to: usize,
+    ) -> Result<Value, Errors> {
+        let mut eval = Eval::new(ast, reader).with_context(std::mem::take(&mut self.context));
+        eval.set_io(Box::new(&mut *self.io));
+        let ret = eval.run_lines(from, to);
+        self.context = eval.into_context();
+        ret
+    }
+
+    // false means quit
+    fn meta(
+        &mut self,
+        cmd: &str,
+        output: &mut impl Write,
+        errors: &mut impl Write,
+    ) -> Result<bool, Errors> {
+        let (cmd, arg) = match cmd.split_once(' ') {
+            Some((cmd, arg)) => (cmd, arg.trim()),
+            None => (cmd, ""),
+        };
+        match cmd {
+            "list" => {
+                let width = self.lines.len().to_string().len();
+                for (i, line) in self.lines.iter().enumerate() {
+                    writeln!(output, "{:>width$} | {}", i + 1, line)
+                        .map_err(|_| Errors::IOError)?;
+                }
+            }
+            "vars" => {
+                let mut vars: Vec<_> = self.context.iter().collect();
+                vars.sort_by(|a, b| a.0.id.cmp(&b.0.id));
+                for (name, value) in vars {
+                    writeln!(output, "{} be {}", name.id, Eval::display(value))
+                        .map_err(|_| Errors::IOError)?;
+                }
+            }
+            "load" => {
+                let result = fs::read_to_string(arg)
+                    .map_err(|_| Errors::IOError)
+                    .and_then(|source| self.load(&source));
+                Self::show(result, output, errors)?;
+            }
+            "reset" => {
+                self.lines.clear();
+                self.context.clear();
+            }
+            "help" => writeln!(output, "{}", HELP).map_err(|_| Errors::IOError)?,
+            "quit" | "exit" => return Ok(false),
+            _ => {
+                writeln!(errors, "WHAT IS :{}? TRY :help", cmd).map_err(|_| Errors::IOError)?
+            }
+        }
+        Ok(true)
+    }
+}
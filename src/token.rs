@@ -1,11 +1,14 @@
-use crate::lexer::Pos;
+use crate::lexer::Span;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum TokenType {
     // Literals
     Id(String),
     String(String),
     Number(u128),
+    // Too big for Number
+    #[cfg(feature = "bigint")]
+    BigNumber(crate::bigint::Big),
 
     // Keywords
     Write,
@@ -21,6 +24,24 @@ pub enum TokenType {
     Over,
     ToThe,
     Mod,
+    Div,
+    Min,
+    Max,
+    Floor,
+    Ceil,
+    Round,
+    Abs,
+    Sqrt,
+    Length,
+    Letter,
+    Part,
+    Find,
+    Upper,
+    Lower,
+    Code,
+    Character,
+    Of,
+    In,
     Is,
     Isnt,
     Below,
@@ -42,69 +63,110 @@ pub enum TokenType {
     Note,
     Neg,
     End,
+    Label,
+    Otherwise,
+    Repeat,
 }
 
 #[derive(Debug)]
 pub struct Token {
     pub token_type: TokenType,
-    pub location: Pos,
+    pub location: Span,
 }
 
+// Every keyword and what it lexes to
+pub const KEYWORDS: &[(&str, TokenType)] = &[
+    ("write", TokenType::Write),
+    ("read", TokenType::Read),
+    ("be", TokenType::Be),
+    ("run", TokenType::Run),
+    ("if", TokenType::If),
+    ("to", TokenType::To),
+    ("dot", TokenType::Dot),
+    ("plus", TokenType::Plus),
+    ("minus", TokenType::Minus),
+    ("times", TokenType::Times),
+    ("over", TokenType::Over),
+    ("mod", TokenType::Mod),
+    ("tothe", TokenType::ToThe),
+    ("div", TokenType::Div),
+    ("min", TokenType::Min),
+    ("max", TokenType::Max),
+    ("floor", TokenType::Floor),
+    ("ceil", TokenType::Ceil),
+    ("round", TokenType::Round),
+    ("abs", TokenType::Abs),
+    ("sqrt", TokenType::Sqrt),
+    ("length", TokenType::Length),
+    ("letter", TokenType::Letter),
+    ("part", TokenType::Part),
+    ("find", TokenType::Find),
+    ("upper", TokenType::Upper),
+    ("lower", TokenType::Lower),
+    ("code", TokenType::Code),
+    ("character", TokenType::Character),
+    ("of", TokenType::Of),
+    ("in", TokenType::In),
+    ("is", TokenType::Is),
+    ("isnt", TokenType::Isnt),
+    ("below", TokenType::Below),
+    ("above", TokenType::Above),
+    ("atmost", TokenType::AtMost),
+    ("atleast", TokenType::AtLeast),
+    ("and", TokenType::And),
+    ("or", TokenType::Or),
+    ("not", TokenType::Not),
+    ("num", TokenType::Num),
+    ("text", TokenType::Text),
+    ("choice", TokenType::Choice),
+    ("yes", TokenType::Yes),
+    ("no", TokenType::No),
+    ("neg", TokenType::Neg),
+    ("close", TokenType::Close),
+    ("open", TokenType::Open),
+    ("note", TokenType::Note),
+    ("end", TokenType::End),
+    ("label", TokenType::Label),
+    ("otherwise", TokenType::Otherwise),
+    ("repeat", TokenType::Repeat),
+];
+
+// Words older programs could use as names, so using one gets its own error
+pub const NEWER_KEYWORDS: &[&str] = &[
+    "div", "min", "max", "floor", "ceil", "round", "abs", "sqrt", "length", "letter", "part",
+    "find", "upper", "lower", "code", "character", "of", "in", "label", "otherwise", "repeat",
+];
+
 impl TokenType {
+    // The word it's written as, if it's a keyword
+    pub fn keyword(&self) -> Option<&'static str> {
+        KEYWORDS
+            .iter()
+            .find(|(_, token_type)| token_type == self)
+            .map(|(keyword, _)| *keyword)
+    }
+
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
-        Some(match s {
-            "write" => Self::Write,
-            "read" => Self::Read,
-            "be" => Self::Be,
-            "run" => Self::Run,
-            "if" => Self::If,
-            "to" => Self::To,
-            "dot" => Self::Dot,
-            "plus" => Self::Plus,
-            "minus" => Self::Minus,
-            "times" => Self::Times,
-            "over" => Self::Over,
-            "mod" => Self::Mod,
-            "tothe" => Self::ToThe,
-            "is" => Self::Is,
-            "isnt" => Self::Isnt,
-            "below" => Self::Below,
-            "above" => Self::Above,
-            "atmost" => Self::AtMost,
-            "atleast" => Self::AtLeast,
-            "and" => Self::And,
-            "or" => Self::Or,
-            "not" => Self::Not,
-            "num" => Self::Num,
-            "text" => Self::Text,
-            "choice" => Self::Choice,
-            "yes" => Self::Yes,
-            "no" => Self::No,
-            "neg" => Self::Neg,
-            "close" => Self::Close,
-            "open" => Self::Open,
-            "note" => Self::Note,
-            "end" => Self::End,
-            _ => return None,
-        })
+        KEYWORDS
+            .iter()
+            .find(|(keyword, _)| *keyword == s)
+            .map(|(_, token_type)| token_type.clone())
     }
 }
 
 impl Token {
-    pub fn new(token_type: TokenType, location: Pos) -> Self {
+    pub fn new(token_type: TokenType, location: Span) -> Self {
         Self {
             token_type,
             location,
         }
     }
 
-    pub fn from_str(lexeme: &str, location: Pos) -> Option<Self> {
-        match TokenType::from_str(&lexeme) {
-            Some(token_type) => Some(Self {
-                token_type,
-                location,
-            }),
-            None => None,
-        }
+    pub fn from_str(lexeme: &str, location: Span) -> Option<Self> {
+        TokenType::from_str(lexeme).map(|token_type| Self {
+            token_type,
+            location,
+        })
     }
 }
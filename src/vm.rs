@@ -0,0 +1,273 @@
+use crate::{
+    ast::*,
+    errors::{Errors, Kind},
+    eval::{Eval, Io, Value},
+    lexer::{Reader, Span},
+};
+
+mod compile;
+
+pub use compile::compile;
+
+// Stack machine instructions
+#[derive(Debug)]
+pub enum Op {
+    // Push constants[i]
+    Const(usize),
+    // Push / pop variable slots
+    Load(usize, Span),
+    Store(usize),
+    Pop,
+
+    Unary(Operator, Span),
+    Binary(Operator, Span),
+    Ternary(Operator, Span),
+    Nullary(Operator, Span),
+    Write,
+
+    // Pop condition, jump if it's not a yes
+    JumpUnless(usize),
+    Jump(usize),
+
+    // 'run' with constant lines: code from, code to, where to come back
+    Call(usize, usize, usize),
+    // 'run' with computed lines, checked when executed: line of the 'run',
+    // where the lines are written
+    RunFrom(usize, Span),
+    RunAt(usize, Span),
+    // Same, with the number of times on top and where that's written
+    RepeatFrom(usize, Span, Span),
+    RepeatAt(usize, Span, Span),
+    // Last 'run' again, number of times on top: line, the 'repeat', times
+    Repeat(usize, Span, Span),
+
+    // Pop into / clear the value of the last line
+    Ret,
+    Clear,
+    End,
+}
+
+/// A program compiled by [`compile`], ready for a [`Vm`].
+#[derive(Debug)]
+pub struct Chunk {
+    pub code: Vec<Op>,
+    pub constants: Vec<Value>,
+    // Slot -> variable name
+    pub names: Vec<String>,
+    // Line n is code[lines[n - 1]..lines[n]]
+    pub lines: Vec<usize>,
+}
+
+/// Runs a [`Chunk`] on a stack, doing what [`Eval`] does with the same
+/// program but faster. There are no hooks or limits.
+pub struct Vm<'a> {
+    chunk: &'a Chunk,
+    // For the operators and runtime errors
+    eval: Eval<'a>,
+    stack: Vec<Value>,
+    slots: Vec<Option<Value>>,
+    // Suspended 'run's: code to come back to, end of the code, start of the
+    // running code and times to go again
+    frames: Vec<(usize, usize, usize, u128)>,
+    // Code of the last 'run', for 'repeat'
+    last_run: Option<(usize, usize)>,
+}
+
+impl<'a> Vm<'a> {
+    /// The chunk, and the program and reader it was compiled from, for
+    /// errors.
+    pub fn new(chunk: &'a Chunk, input: &'a Vec<Stmt>, reader: &'a Reader) -> Self {
+        Self {
+            chunk,
+            eval: Eval::new(input, reader),
+            stack: Vec::new(),
+            slots: vec![None; chunk.names.len()],
+            frames: Vec::new(),
+            last_run: None,
+        }
+    }
+
+    /// Where `write` and `read` go instead of the terminal.
+    pub fn set_io(&mut self, io: Box<dyn Io + 'a>) {
+        self.eval.set_io(io);
+    }
+
+    /// Runs the whole program, giving the value of the last statement run.
+    pub fn run_prog(&mut self) -> Result<Value, Errors> {
+        let mut ret = Value::Nothing;
+        let code = &self.chunk.code;
+        let mut pc = 0;
+        let mut end = code.len();
+        self.frames.clear();
+
+        loop {
+            if pc >= end {
+                match self.frames.last_mut() {
+                    Some((_, _, start, left)) if *left > 0 => {
+                        *left -= 1;
+                        pc = *start;
+                    }
+                    Some(&mut (back, back_end, ..)) => {
+                        self.frames.pop();
+                        (pc, end) = (back, back_end);
+                    }
+                    None => break,
+                }
+                continue;
+            }
+
+            let op = &code[pc];
+            pc += 1;
+            match op {
+                Op::Const(i) => self.stack.push(self.chunk.constants[*i].clone()),
+                Op::Load(slot, location) => match &self.slots[*slot] {
+                    Some(v) => self.stack.push(v.clone()),
+                    None => {
+                        return Err(self.eval.runtime_error(
+                            Kind::VariableNotFound,
+                            format!("VARIABLE {} NOT FOUND", self.chunk.names[*slot]),
+                            *location,
+                        ))
+                    }
+                },
+                Op::Store(slot) => self.slots[*slot] = Some(self.pop()),
+                Op::Pop => {
+                    self.pop();
+                }
+                Op::Unary(op, location) => {
+                    let operand = self.pop();
+                    let v = self.eval.unary(op, &operand, *location)?;
+                    self.stack.push(v);
+                }
+                Op::Binary(op, location) => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    let v = self.eval.binary(op, &left, &right, *location)?;
+                    self.stack.push(v);
+                }
+                Op::Ternary(op, location) => {
+                    let third = self.pop();
+                    let second = self.pop();
+                    let first = self.pop();
+                    let v = self.eval.ternary(op, &first, &second, &third, *location)?;
+                    self.stack.push(v);
+                }
+                Op::Nullary(op, location) => {
+                    let v = self.eval.nullary(op, *location)?;
+                    self.stack.push(v);
+                }
+                Op::Write => {
+                    let v = self.pop();
+                    self.eval.write_value(&v)?;
+                }
+                Op::JumpUnless(target) => {
+                    let cond = self.pop();
+                    if let Value::Choice(false) = self.eval.choice(&cond) {
+                        pc = *target;
+                    }
+                }
+                Op::Jump(target) => pc = *target,
+                Op::Call(from, to, back) => {
+                    self.last_run = Some((*from, *to));
+                    self.push_frame(*back, end, *from, 0);
+                    (pc, end) = (*from, *to);
+                }
+                Op::RunFrom(line, location) | Op::RepeatFrom(line, location, _) => {
+                    let times = match op {
+                        Op::RepeatFrom(.., times) => Some((self.pop(), *times)),
+                        _ => None,
+                    };
+                    let to = self.pop();
+                    let from = self.pop();
+                    let (kind, msg) = (Kind::InvalidLineRange, "INVALID LINE RANGE");
+                    let lines = self.lines(from, to, *location, kind, msg)?;
+                    (pc, end) = self.call(lines, times, *line, end)?;
+                }
+                Op::RunAt(line, location) | Op::RepeatAt(line, location, _) => {
+                    let times = match op {
+                        Op::RepeatAt(.., times) => Some((self.pop(), *times)),
+                        _ => None,
+                    };
+                    let at = self.pop();
+                    let (kind, msg) = (Kind::InvalidLineNumber, "INVALID LINE NUMBER");
+                    let lines = self.lines(at.clone(), at, *location, kind, msg)?;
+                    (pc, end) = self.call(lines, times, *line, end)?;
+                }
+                Op::Repeat(line, location, times) => {
+                    let times = (self.pop(), *times);
+                    let Some(lines) = self.last_run else {
+                        return Err(self.eval.runtime_error(
+                            Kind::NothingToRepeat,
+                            "NOTHING TO REPEAT".to_string(),
+                            *location,
+                        ));
+                    };
+                    (pc, end) = self.call(lines, Some(times), *line, end)?;
+                }
+                Op::Ret => ret = self.pop(),
+                Op::Clear => ret = Value::Nothing,
+                Op::End => break,
+            }
+        }
+        Ok(ret)
+    }
+
+    // Start running code, maybe more than once. Returns the new pc and end.
+    fn call(
+        &mut self,
+        (from, to): (usize, usize),
+        times: Option<(Value, Span)>,
+        line: usize,
+        end: usize,
+    ) -> Result<(usize, usize), Errors> {
+        let times = match times {
+            Some((times, location)) => self.eval.repeat_count(times, location)?,
+            None => 1,
+        };
+        self.last_run = Some((from, to));
+        // Like a 'run' the rest of the line is skipped, even if it's 0 times
+        let back = self.chunk.lines[line];
+        if times == 0 {
+            return Ok((back, end));
+        }
+        self.push_frame(back, end, from, times - 1);
+        Ok((from, to))
+    }
+
+    // A 'run' with nothing after it takes the place of what it was in, like
+    // Eval does, so loops made of 'run's don't pile up
+    fn push_frame(&mut self, mut back: usize, mut end: usize, start: usize, left: u128) {
+        if back >= end {
+            if let Some(&(outer_back, outer_end, _, 0)) = self.frames.last() {
+                self.frames.pop();
+                (back, end) = (outer_back, outer_end);
+            }
+        }
+        self.frames.push((back, end, start, left));
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack.pop().expect("EMPTY VM STACK")
+    }
+
+    // Line range -> code range, same rules as Eval::run_from
+    fn lines(
+        &self,
+        from: Value,
+        to: Value,
+        location: Span,
+        kind: Kind,
+        msg: &str,
+    ) -> Result<(usize, usize), Errors> {
+        let len = self.chunk.lines.len() as i128 - 1;
+        if let (Value::Int(from), Value::Int(to)) = (from, to) {
+            if from <= to && from >= 1 && to <= len {
+                return Ok((
+                    self.chunk.lines[from as usize - 1],
+                    self.chunk.lines[to as usize],
+                ));
+            }
+        }
+        Err(self.eval.runtime_error(kind, msg.to_string(), location))
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use super::*;
+
+struct Compiler<'a> {
+    prog: &'a [Stmt],
+    chunk: Chunk,
+    slots: HashMap<String, usize>,
+    // Constant 'run's to patch once every line has its code: op, from, to, line
+    calls: Vec<(usize, usize, usize, usize)>,
+}
+
+/// Turns a parsed program into code for a [`Vm`](super::Vm). Never fails:
+/// what can go wrong is checked when the code runs.
+pub fn compile(prog: &[Stmt]) -> Chunk {
+    let mut compiler = Compiler {
+        prog,
+        chunk: Chunk {
+            code: Vec::new(),
+            constants: Vec::new(),
+            names: Vec::new(),
+            lines: vec![0],
+        },
+        slots: HashMap::new(),
+        calls: Vec::new(),
+    };
+
+    for (i, stmt) in prog.iter().enumerate() {
+        compiler.line(stmt, i + 1);
+        compiler.chunk.lines.push(compiler.chunk.code.len());
+    }
+
+    // Jump targets are known now
+    let Compiler {
+        mut chunk, calls, ..
+    } = compiler;
+    for (op, from, to, line) in calls {
+        chunk.code[op] = Op::Call(chunk.lines[from - 1], chunk.lines[to], chunk.lines[line]);
+    }
+    chunk
+}
+
+impl<'a> Compiler<'a> {
+    fn emit(&mut self, op: Op) -> usize {
+        self.chunk.code.push(op);
+        self.chunk.code.len() - 1
+    }
+
+    // The value of a line is only kept for plain expressions, like in Eval::run_prog
+    fn line(&mut self, stmt: &Stmt, line: usize) {
+        match stmt {
+            Stmt::Expr(expr) => {
+                self.expr(expr);
+                self.emit(Op::Ret);
+            }
+            _ => {
+                self.stmt(stmt, line);
+                self.emit(Op::Clear);
+            }
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt, line: usize) {
+        match stmt {
+            Stmt::Expr(expr) => {
+                self.expr(expr);
+                self.emit(Op::Pop);
+            }
+            Stmt::Write(expr, _) => {
+                self.expr(expr);
+                self.emit(Op::Write);
+            }
+            Stmt::RunFrom(from, to, _) => match (self.line_number(from), self.line_number(to)) {
+                (Some(from), Some(to)) if from <= to => {
+                    let op = self.emit(Op::Call(0, 0, 0));
+                    self.calls.push((op, from, to, line));
+                }
+                _ => {
+                    self.expr(from);
+                    self.expr(to);
+                    self.emit(Op::RunFrom(line, from.span().to(to.span())));
+                }
+            },
+            Stmt::RunAt(at, _) => match self.line_number(at) {
+                Some(at) => {
+                    let op = self.emit(Op::Call(0, 0, 0));
+                    self.calls.push((op, at, at, line));
+                }
+                None => {
+                    self.expr(at);
+                    self.emit(Op::RunAt(line, at.span()));
+                }
+            },
+            Stmt::Repeat(run, times, span) => match run.as_deref() {
+                Some(Stmt::RunFrom(from, to, _)) => {
+                    self.expr(from);
+                    self.expr(to);
+                    self.expr(times);
+                    let lines = from.span().to(to.span());
+                    self.emit(Op::RepeatFrom(line, lines, times.span()));
+                }
+                Some(Stmt::RunAt(at, _)) => {
+                    self.expr(at);
+                    self.expr(times);
+                    self.emit(Op::RepeatAt(line, at.span(), times.span()));
+                }
+                _ => {
+                    self.expr(times);
+                    self.emit(Op::Repeat(line, *span, times.span()));
+                }
+            },
+            Stmt::Switch(stmt, cond, otherwise, _) => {
+                self.expr(cond);
+                let jump = self.emit(Op::JumpUnless(0));
+                self.stmt(stmt, line);
+                match otherwise {
+                    Some(otherwise) => {
+                        let skip = self.emit(Op::Jump(0));
+                        self.chunk.code[jump] = Op::JumpUnless(self.chunk.code.len());
+                        self.stmt(otherwise, line);
+                        self.chunk.code[skip] = Op::Jump(self.chunk.code.len());
+                    }
+                    None => self.chunk.code[jump] = Op::JumpUnless(self.chunk.code.len()),
+                }
+            }
+            Stmt::Asgn(id, expr, _) => {
+                let Expr::Id(id, _) = id.as_ref() else {
+                    panic!("LEFT SIDE NOT ID");
+                };
+                self.expr(expr);
+                let slot = self.slot(id);
+                self.emit(Op::Store(slot));
+            }
+            Stmt::Blank => (),
+            Stmt::End => {
+                self.emit(Op::End);
+            }
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Binary(op, left, right, location) => {
+                self.expr(left);
+                self.expr(right);
+                self.emit(Op::Binary(op.clone(), *location));
+            }
+            Expr::Ternary(op, first, second, third, location) => {
+                self.expr(first);
+                self.expr(second);
+                self.expr(third);
+                self.emit(Op::Ternary(op.clone(), *location));
+            }
+            Expr::Unary(op, operand, location) => {
+                self.expr(operand);
+                self.emit(Op::Unary(op.clone(), *location));
+            }
+            Expr::Nullary(op, location) => {
+                self.emit(Op::Nullary(op.clone(), *location));
+            }
+            Expr::Literal(literal, _) => {
+                self.chunk.constants.push(Eval::literal(literal));
+                self.emit(Op::Const(self.chunk.constants.len() - 1));
+            }
+            Expr::Id(id, location) => {
+                let slot = self.slot(id);
+                self.emit(Op::Load(slot, *location));
+            }
+        }
+    }
+
+    fn slot(&mut self, id: &str) -> usize {
+        if let Some(slot) = self.slots.get(id) {
+            return *slot;
+        }
+        self.chunk.names.push(id.to_string());
+        self.slots
+            .insert(id.to_string(), self.chunk.names.len() - 1);
+        self.chunk.names.len() - 1
+    }
+
+    // Literal line inside the program, anything else is left for runtime
+    fn line_number(&self, expr: &Expr) -> Option<usize> {
+        match expr {
+            Expr::Literal(Literal::Integer(n), _) if *n >= 1 && *n <= self.prog.len() as u128 => {
+                Some(*n as usize)
+            }
+            Expr::Literal(Literal::Label(_, line), _) => Some(*line),
+            _ => None,
+        }
+    }
+}
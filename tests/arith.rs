@@ -0,0 +1,70 @@
+use nooblang_rust::{eval::Buffer, vm, Errors, Eval, Reader};
+
+// What each engine wrote, or the error it stopped with
+fn both(source: &str) -> [Result<String, String>; 2] {
+    let mut reader = Reader::from(source);
+    let ast = nooblang_rust::parse(&mut reader).unwrap();
+    let finish = |result: Result<_, Errors>, io: Buffer| match result {
+        Ok(_) => Ok(io.output().to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+
+    let mut io = Buffer::new("");
+    let mut eval = Eval::new(&ast, &reader);
+    eval.set_io(Box::new(&mut io));
+    let result = eval.run_prog();
+    drop(eval);
+    let eval = finish(result, io);
+
+    let chunk = vm::compile(&ast);
+    let mut io = Buffer::new("");
+    let mut vm = vm::Vm::new(&chunk, &ast, &reader);
+    vm.set_io(Box::new(&mut io));
+    let result = vm.run_prog();
+    drop(vm);
+    [eval, finish(result, io)]
+}
+
+#[test]
+fn mod_by_zero_is_an_error() {
+    for source in ["write 5 mod 0", "write 1 over 2 mod 0", "write 5 dot 5 mod 0 dot 0"] {
+        for result in both(source) {
+            let e = result.unwrap_err();
+            assert!(e.contains("CAN'T MODOLO BY ZERO"), "{}: {}", source, e);
+        }
+    }
+}
+
+#[test]
+fn mod_still_works() {
+    for result in both("write 7 mod 3\nwrite neg 7 mod 3\nwrite 7 over 2 mod 1") {
+        assert_eq!(result.unwrap(), "1\n-1\n0.5\n");
+    }
+}
+
+#[test]
+fn div_by_zero_is_an_error() {
+    for source in ["write 7 div 0", "write 7 over 2 div 0", "write 7 div 0 dot 0"] {
+        for result in both(source) {
+            let e = result.unwrap_err();
+            assert!(e.contains("CAN'T DIVIDE BY ZERO"), "{}: {}", source, e);
+        }
+    }
+    for result in both("write 7 div 2\nwrite neg 7 div 2\nwrite 15 over 2 div 2") {
+        assert_eq!(result.unwrap(), "3\n-3\n3\n");
+    }
+}
+
+#[test]
+fn over_zero_is_an_error() {
+    for source in ["write 1 over 0", "write 0 over 0", "write 1 over 2 over 0", "write 1 over 0 dot 0"] {
+        for result in both(source) {
+            let e = result.unwrap_err();
+            assert!(e.contains("CAN'T DIVIDE BY ZERO"), "{}: {}", source, e);
+        }
+    }
+    // Anything else is still exact
+    for result in both("write 1 over 2 dot 5\nwrite 6 over 4") {
+        assert_eq!(result.unwrap(), "0.4\n1.5\n");
+    }
+}
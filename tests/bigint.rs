@@ -0,0 +1,158 @@
+#![cfg(feature = "bigint")]
+
+use nooblang_rust::{bigint::Big, eval::Buffer, vm, Eval, Reader};
+
+fn big(digits: &str) -> Big {
+    Big::parse(digits).unwrap()
+}
+
+// What each engine wrote
+fn both(source: &str) -> [String; 2] {
+    let mut reader = Reader::from(source);
+    let ast = nooblang_rust::parse(&mut reader).unwrap();
+
+    let mut io = Buffer::new("");
+    let mut eval = Eval::new(&ast, &reader);
+    eval.set_io(Box::new(&mut io));
+    eval.run_prog().unwrap();
+    drop(eval);
+    let from_eval = io.output().to_string();
+
+    let chunk = vm::compile(&ast);
+    let mut io = Buffer::new("");
+    let mut vm = vm::Vm::new(&chunk, &ast, &reader);
+    vm.set_io(Box::new(&mut io));
+    vm.run_prog().unwrap();
+    drop(vm);
+    [from_eval, io.output().to_string()]
+}
+
+#[test]
+fn parse_and_display_round_trip() {
+    for digits in [
+        "0",
+        "1",
+        "-1",
+        "999999999",
+        "1000000000",
+        "4294967295",
+        "4294967296",
+        "-18446744073709551616",
+        "123456789012345678901234567890123456789012345678901234567890",
+    ] {
+        assert_eq!(big(digits).to_string(), digits);
+    }
+    // Zeros in the middle of a chunk of nine stay
+    assert_eq!(big("1000000000000000001").to_string(), "1000000000000000001");
+    assert_eq!(big("000123").to_string(), "123");
+    assert_eq!(big("-0").to_string(), "0");
+
+    for bad in ["", "-", "1.5", "12a", "+1", " 1"] {
+        assert!(Big::parse(bad).is_none(), "{:?}", bad);
+    }
+}
+
+#[test]
+fn i128_boundary() {
+    for n in [i128::MIN, i128::MIN + 1, -1, 0, 1, i128::MAX] {
+        let b = Big::from(n);
+        assert_eq!(b.to_string(), n.to_string());
+        assert_eq!(b.to_i128(), Some(n));
+        assert_eq!(big(&n.to_string()), b);
+    }
+
+    let one = Big::from(1);
+    let past_max = &Big::from(i128::MAX) + &one;
+    assert_eq!(past_max.to_string(), "170141183460469231731687303715884105728");
+    assert_eq!(past_max.to_i128(), None);
+    assert_eq!((&past_max - &one).to_i128(), Some(i128::MAX));
+
+    let past_min = &Big::from(i128::MIN) - &one;
+    assert_eq!(past_min.to_i128(), None);
+    assert_eq!((&past_min + &one).to_i128(), Some(i128::MIN));
+    // Fits in 128 bits without a sign, but not with one
+    assert_eq!((-&past_max).to_i128(), Some(i128::MIN));
+}
+
+#[test]
+fn overflow_into_big_and_back() {
+    let source = "top be 170141183460469231731687303715884105727\n\
+                  x be top plus 1\n\
+                  write x\n\
+                  write x minus 1 is top\n\
+                  write neg top minus 2\n\
+                  write neg top minus 2 plus 2\n\
+                  write top times top over top";
+    let expected = "170141183460469231731687303715884105728\n\
+                    yes\n\
+                    -170141183460469231731687303715884105729\n\
+                    -170141183460469231731687303715884105727\n\
+                    170141183460469231731687303715884105727\n";
+    assert_eq!(both(source), [expected, expected]);
+}
+
+#[test]
+fn div_and_rem_with_mixed_signs() {
+    // Same as i128: towards 0, and what's left has the first one's sign
+    for (a, b) in [(7, 2), (-7, 2), (7, -2), (-7, -2), (6, 3), (-6, 3), (1, 7), (-1, 7)] {
+        let (x, y) = (Big::from(a), Big::from(b));
+        assert_eq!((&x / &y).to_i128(), Some(a / b), "{} / {}", a, b);
+        assert_eq!((&x % &y).to_i128(), Some(a % b), "{} % {}", a, b);
+    }
+
+    // Past i128, and a whole number of times
+    let a = big("-1000000000000000000000000000000000000000007");
+    let b = big("1000000000000000000000");
+    assert_eq!((&a / &b).to_string(), "-1000000000000000000000");
+    assert_eq!((&a % &b).to_string(), "-7");
+    assert_eq!((&a / &-&b).to_string(), "1000000000000000000000");
+    assert_eq!((&a % &-&b).to_string(), "-7");
+    let c = &b * &b;
+    assert_eq!((&c / &b), b);
+    assert!((&c % &b).is_zero());
+}
+
+#[test]
+fn negating_zero_is_zero() {
+    let zero = Big::from(0);
+    assert_eq!(-&zero, zero);
+    assert_eq!((-&zero).to_string(), "0");
+    // Answers that come out 0 don't keep a sign either
+    let n = big("-123456789012345678901234567890");
+    assert_eq!(&n - &n, zero);
+    assert_eq!(&n * &zero, zero);
+    assert_eq!((&n % &Big::from(3)).to_string(), "0");
+}
+
+#[test]
+fn sqrt_rounds_down() {
+    for n in [0, 1, 2, 3, 4, 15, 16, 17, 99, 100, 101] {
+        let root = Big::from(n).sqrt().to_i128().unwrap();
+        assert_eq!(root, (n as f64).sqrt() as i128, "sqrt {}", n);
+    }
+
+    let root = big("123456789012345678901234567890");
+    let square = &root * &root;
+    assert_eq!(square.sqrt(), root);
+    assert_eq!((&square + &root).sqrt(), root);
+    assert_eq!((&square - &Big::from(1)).sqrt(), &root - &Big::from(1));
+}
+
+#[test]
+fn known_values() {
+    let mut factorial = Big::from(1);
+    for n in 1..=40 {
+        factorial = &factorial * &Big::from(n);
+    }
+    assert_eq!(factorial.to_string(), "815915283247897734345611269596115894272000000000");
+
+    assert_eq!(Big::from(2).pow(128).to_string(), "340282366920938463463374607431768211456");
+    assert_eq!(Big::from(-3).pow(3).to_string(), "-27");
+    assert_eq!(Big::from(2).pow(100).bits(), 101);
+
+    let [from_eval, from_vm] = both(
+        "n be 1\nf be 1\nf be f times n\nn be n plus 1\nrun 3 to 5 if n atmost 40\nwrite f",
+    );
+    assert_eq!(from_eval, "815915283247897734345611269596115894272000000000\n");
+    assert_eq!(from_vm, from_eval);
+}
@@ -0,0 +1,27 @@
+use std::path::Path;
+
+use nooblang_rust::{coverage::Coverage, golden};
+
+#[test]
+fn lcov_reads_back_and_adds_up() {
+    let path = Path::new("examples/codes.noob");
+    let mut coverage = Coverage::new();
+    golden::run(path, false, Some(&mut coverage)).unwrap();
+    let once = coverage.to_lcov();
+    assert!(once.contains("DA:3,3\n"));
+    assert!(once.contains("BRDA:5,0,0,2\nBRDA:5,0,1,1\n"));
+
+    let mut again = Coverage::from_lcov(&once).unwrap();
+    assert_eq!(again.to_lcov(), once);
+    golden::run(path, false, Some(&mut again)).unwrap();
+    let twice = again.to_lcov();
+    assert!(twice.contains("DA:3,6\n"));
+    assert!(twice.contains("BRDA:5,0,0,4\nBRDA:5,0,1,2\n"));
+}
+
+#[test]
+fn not_lcov_is_refused() {
+    assert!(Coverage::from_lcov("garbage\n").is_none());
+    assert!(Coverage::from_lcov("SF:a.noob\nDA:1,1\n").is_none());
+    assert!(Coverage::from_lcov("").is_some());
+}
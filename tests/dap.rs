@@ -0,0 +1,88 @@
+use nooblang_rust::{
+    dap,
+    json::{self, Json, Message},
+};
+
+#[test]
+fn bad_messages_are_skipped_and_the_session_goes_on() {
+    let mut input = Vec::new();
+    input.extend_from_slice(b"Content-Length: 5\r\n\r\n{oops");
+    input.extend_from_slice(b"Content-Type: x\r\n\r\n");
+    let initialize = Json::object(vec![
+        ("seq", 1.into()),
+        ("type", "request".into()),
+        ("command", "initialize".into()),
+    ]);
+    json::write_message(&mut input, &initialize).unwrap();
+
+    let mut output = Vec::new();
+    dap::serve(&input[..], &mut output).unwrap();
+    let mut output = &output[..];
+    let Some(Message::Json(response)) = json::read_message(&mut output).unwrap() else {
+        panic!("no answer");
+    };
+    assert_eq!(response.get("command").as_str(), Some("initialize"));
+    assert_eq!(response.get("success").as_bool(), Some(true));
+}
+
+// Launches the program with the arguments and gives back everything it sent
+// to the console, stdout and stderr
+fn console(source: &str, mut arguments: Vec<(&str, Json)>) -> (String, String) {
+    // Tests run at the same time, so each program gets its own file
+    let name = format!("nooblang-dap-{}-{}.noob", std::process::id(), source.len());
+    let path = std::env::temp_dir().join(name);
+    std::fs::write(&path, source).unwrap();
+    arguments.push(("program", path.display().to_string().into()));
+
+    let mut input = Vec::new();
+    for (seq, (command, arguments)) in [
+        ("launch", Json::object(arguments)),
+        ("configurationDone", Json::object(vec![])),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let request = Json::object(vec![
+            ("seq", (seq + 1).into()),
+            ("type", "request".into()),
+            ("command", command.into()),
+            ("arguments", arguments),
+        ]);
+        json::write_message(&mut input, &request).unwrap();
+    }
+
+    let mut output = Vec::new();
+    dap::serve(&input[..], &mut output).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let (mut stdout, mut stderr) = (String::new(), String::new());
+    let mut output = &output[..];
+    while let Some(Message::Json(message)) = json::read_message(&mut output).unwrap() {
+        if message.get("event").as_str() != Some("output") {
+            continue;
+        }
+        let body = message.get("body");
+        let text = body.get("output").as_str().unwrap();
+        match body.get("category").as_str() {
+            Some("stdout") => stdout.push_str(text),
+            _ => stderr.push_str(text),
+        }
+    }
+    (stdout, stderr)
+}
+
+#[test]
+fn read_gets_the_launch_input() {
+    let source = "write \"hi \" plus read\nwrite num read plus 1\nwrite read";
+    let (stdout, stderr) = console(source, vec![("input", "there\n41".into())]);
+    // Empty once there's no more, like anywhere else
+    assert_eq!(stdout, "hi there\n42\n\n");
+    assert_eq!(stderr, "");
+}
+
+#[test]
+fn read_without_input_is_an_error() {
+    let (stdout, stderr) = console("write 1\nx be read\nwrite 2", vec![]);
+    assert_eq!(stdout, "1\n");
+    assert!(stderr.contains("CAN'T READ WHILE DEBUGGING"), "{}", stderr);
+}
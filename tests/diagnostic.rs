@@ -0,0 +1,157 @@
+use std::time::Duration;
+
+use nooblang_rust::{
+    diagnostic::Diagnostic,
+    eval::{Buffer, Limits},
+    vm, Errors, Eval, Reader, Value,
+};
+
+// The codes of the errors that stopped a program from being read
+fn syntax_codes(source: &str) -> Vec<&'static str> {
+    let mut reader = Reader::from(source);
+    match nooblang_rust::parse(&mut reader) {
+        Ok(_) => Vec::new(),
+        Err(e) => Diagnostic::all(&e).iter().map(|d| d.code).collect(),
+    }
+}
+
+fn code(result: Result<Value, Errors>) -> Option<&'static str> {
+    result.err().map(|e| Diagnostic::from_error(&e).code)
+}
+
+// The code of the error the evaluator stopped with, under the limits
+fn limited_code(source: &str, limits: Limits) -> Option<&'static str> {
+    let mut reader = Reader::from(source);
+    let ast = nooblang_rust::parse(&mut reader).unwrap();
+    let mut io = Buffer::new("");
+    let mut eval = Eval::new(&ast, &reader);
+    eval.set_io(Box::new(&mut io));
+    eval.set_limits(limits);
+    code(eval.run_prog())
+}
+
+// The code of the error each engine stopped with
+fn runtime_codes(source: &str) -> [Option<&'static str>; 2] {
+    let mut reader = Reader::from(source);
+    let ast = nooblang_rust::parse(&mut reader).unwrap();
+    let chunk = vm::compile(&ast);
+    let mut io = Buffer::new("");
+    let mut vm = vm::Vm::new(&chunk, &ast, &reader);
+    vm.set_io(Box::new(&mut io));
+    [limited_code(source, Limits::default()), code(vm.run_prog())]
+}
+
+#[test]
+fn lexer_errors_have_their_codes() {
+    let cases = [
+        ("write 1 + 2", "E101"),
+        ("write \"hi", "E102"),
+        ("write 1\rwrite 2", "E104"),
+        ("x be \"\"\"\nnever ends", "E105"),
+        ("write \"\\quote\"", "E106"),
+        ("write \"\\nope\\\"", "E107"),
+        ("write \"\\unicode D800\\\"", "E108"),
+    ];
+    for (source, code) in cases {
+        assert_eq!(syntax_codes(source), [code], "{:?}", source);
+    }
+}
+
+#[cfg(not(feature = "bigint"))]
+#[test]
+fn number_too_large_has_its_code() {
+    let source = "write 999999999999999999999999999999999999999999";
+    assert_eq!(syntax_codes(source), ["E103"]);
+}
+
+// E116 and E117 are only made when the parser was wrong about what comes
+// next, so no program gets them
+#[test]
+fn parser_errors_have_their_codes() {
+    let cases = [
+        ("write 1 2", "E111"),
+        ("write", "E112"),
+        ("write open 1", "E113"),
+        ("1 be 2", "E114"),
+        ("write 1 dot x", "E115"),
+        ("label", "E118"),
+        ("label a\nlabel a", "E119"),
+        ("write length 1", "E120"),
+        ("write part 1 2 of \"a\"", "E121"),
+        ("write find \"a\" \"b\"", "E122"),
+        ("min be 1", "E123"),
+    ];
+    for (source, code) in cases {
+        assert_eq!(syntax_codes(source), [code], "{:?}", source);
+    }
+}
+
+#[test]
+fn every_syntax_error_keeps_its_code() {
+    assert_eq!(syntax_codes("write 1 + 2\nwrite\n1 be 2"), ["E101", "E112", "E114"]);
+}
+
+#[test]
+fn runtime_errors_have_their_codes() {
+    let cases = [
+        ("write x", "E201"),
+        ("run 2 to 1", "E202"),
+        ("run 5", "E203"),
+        ("write 1 below \"a\"", "E204"),
+        ("write 1 plus yes", "E205"),
+        ("write \"a\" minus 1", "E206"),
+        ("write \"a\" times 2", "E207"),
+        ("write \"a\" over 2", "E208"),
+        ("write \"a\" tothe 2", "E209"),
+        ("write \"a\" mod 2", "E210"),
+        ("write neg \"a\"", "E211"),
+        ("repeat 2", "E212"),
+        ("write 1\nrun 1 repeat \"a\"", "E213"),
+        ("write round \"a\"", "E214"),
+        ("write abs \"a\"", "E215"),
+        ("write sqrt neg 1", "E216"),
+        ("write length of 1", "E217"),
+        ("write letter 1 of 2", "E218"),
+        ("write letter 5 of \"ab\"", "E219"),
+        ("write part 2 to 9 of \"ab\"", "E220"),
+        ("write find 1 in \"a\"", "E221"),
+        ("write upper 1", "E222"),
+        ("write code of \"\"", "E223"),
+        ("write character neg 1", "E224"),
+        ("write 1 mod 0", "E228"),
+        ("write 1 div 0", "E229"),
+    ];
+    for (source, code) in cases {
+        let codes = runtime_codes(source);
+        assert_eq!(codes, [Some(code); 2], "{:?}", source);
+    }
+}
+
+#[test]
+fn limit_errors_have_their_codes() {
+    let forever = "write 1\nrun 1 to 2";
+    let limits = Limits {
+        steps: Some(10),
+        ..Limits::default()
+    };
+    assert_eq!(limited_code(forever, limits), Some("E225"));
+    let limits = Limits {
+        time: Some(Duration::ZERO),
+        ..Limits::default()
+    };
+    assert_eq!(limited_code(forever, limits), Some("E226"));
+
+    let deep = "i be 0\ni be i plus 1\nrun 2 to 4 if i below 10\nwrite i";
+    let limits = Limits {
+        depth: Some(3),
+        ..Limits::default()
+    };
+    assert_eq!(limited_code(deep, limits), Some("E227"));
+}
+
+#[test]
+fn other_errors_have_the_general_codes() {
+    let e = Errors::ArgumentError("NO FILE".to_string());
+    assert_eq!(Diagnostic::from_error(&e).code, "E001");
+    assert_eq!(Diagnostic::from_error(&Errors::IOError).code, "E000");
+}
@@ -0,0 +1,19 @@
+use std::path::PathBuf;
+
+use nooblang_rust::golden::{self, Outcome};
+
+// Every example still does what its .out and .err say, on both engines, so
+// the README's examples can't drift from what nooblang does
+#[test]
+fn examples_match_their_golden_files() {
+    let paths = golden::find(&[PathBuf::from("examples")]).unwrap();
+    assert!(!paths.is_empty());
+    for use_vm in [false, true] {
+        for path in &paths {
+            match golden::check(path, use_vm, false, None).unwrap() {
+                Outcome::Pass => (),
+                outcome => panic!("{} (vm: {}): {:?}", path.display(), use_vm, outcome),
+            }
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use nooblang_rust::golden::{self, Outcome};
+
+// A directory of its own for each test, empty to start with
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("nooblang-golden-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write(dir: &Path, file: &str, text: &str) -> PathBuf {
+    let path = dir.join(file);
+    fs::write(&path, text).unwrap();
+    path
+}
+
+fn lines(diff: &[&str]) -> Vec<String> {
+    diff.iter().map(|line| line.to_string()).collect()
+}
+
+#[test]
+fn diff_keeps_the_lines_in_common() {
+    assert_eq!(golden::diff("a\nb\nc\n", "a\nx\nc\n"), lines(&["  a", "- b", "+ x", "  c"]));
+    // Added and taken away
+    assert_eq!(golden::diff("a\nc\n", "a\nb\nc\n"), lines(&["  a", "+ b", "  c"]));
+    assert_eq!(golden::diff("a\nb\nc\n", "a\nc\n"), lines(&["  a", "- b", "  c"]));
+    assert_eq!(golden::diff("", "a\n"), lines(&["+ a"]));
+    assert_eq!(golden::diff("a\n", ""), lines(&["- a"]));
+    // The longest run wins, even if it's further along
+    assert_eq!(
+        golden::diff("x\na\nb\nc\n", "a\nb\nc\nx\n"),
+        lines(&["- x", "  a", "  b", "  c", "+ x"])
+    );
+}
+
+#[test]
+fn diff_points_out_a_missing_newline() {
+    // Only said when there's nothing else to show
+    assert_eq!(golden::diff("a\n", "a"), lines(&["  a", "(DIFFERENT NEWLINE AT THE END)"]));
+}
+
+#[test]
+fn check_compares_output_and_error() {
+    let dir = scratch("check");
+    let prog = write(&dir, "prog.noob", "write num read times 2\nwrite x");
+    write(&dir, "prog.in", "21\n");
+    write(&dir, "prog.out", "42\n");
+    let error = golden::run(&prog, false, None).unwrap().error;
+    assert!(error.contains("VARIABLE x NOT FOUND"), "{}", error);
+    write(&dir, "prog.err", &error);
+
+    for use_vm in [false, true] {
+        assert!(matches!(golden::check(&prog, use_vm, false, None).unwrap(), Outcome::Pass));
+    }
+
+    // Wrong output
+    write(&dir, "prog.out", "41\n");
+    match golden::check(&prog, false, false, None).unwrap() {
+        Outcome::Fail(diff) => assert_eq!(diff, lines(&["OUTPUT:", "- 41", "+ 42"])),
+        outcome => panic!("{:?}", outcome),
+    }
+
+    // No .err expects no error
+    write(&dir, "prog.out", "42\n");
+    fs::remove_file(dir.join("prog.err")).unwrap();
+    match golden::check(&prog, false, false, None).unwrap() {
+        Outcome::Fail(diff) => {
+            assert_eq!(diff[0], "ERROR:");
+            assert!(diff[1..].iter().all(|line| line.starts_with("+ ")), "{:?}", diff);
+        }
+        outcome => panic!("{:?}", outcome),
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn bless_writes_down_what_happened() {
+    let dir = scratch("bless");
+    let prog = write(&dir, "prog.noob", "write \"hi \" plus read");
+    write(&dir, "prog.in", "there");
+    write(&dir, "prog.err", "AN OLD ERROR");
+
+    assert!(matches!(golden::check(&prog, false, true, None).unwrap(), Outcome::Blessed));
+    assert_eq!(fs::read_to_string(dir.join("prog.out")).unwrap(), "hi there\n");
+    // It ended fine, so there's no error to expect
+    assert!(!dir.join("prog.err").exists());
+    assert!(matches!(golden::check(&prog, true, false, None).unwrap(), Outcome::Pass));
+
+    // And now it fails, so there is
+    write(&dir, "prog.noob", "write 1\nwrite 1 over 0");
+    assert!(matches!(golden::check(&prog, true, true, None).unwrap(), Outcome::Blessed));
+    assert_eq!(fs::read_to_string(dir.join("prog.out")).unwrap(), "1\n");
+    let error = fs::read_to_string(dir.join("prog.err")).unwrap();
+    assert!(error.contains("DIVIDE BY ZERO"), "{}", error);
+    assert!(matches!(golden::check(&prog, false, false, None).unwrap(), Outcome::Pass));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn find_goes_into_directories_in_order() {
+    let dir = scratch("find");
+    fs::create_dir(dir.join("inner")).unwrap();
+    write(&dir, "b.noob", "");
+    write(&dir, "a.noob", "");
+    write(&dir, "a.out", "");
+    write(&dir, "inner/c.noob", "");
+
+    let found = golden::find(std::slice::from_ref(&dir)).unwrap();
+    assert_eq!(found, [dir.join("a.noob"), dir.join("b.noob"), dir.join("inner/c.noob")]);
+    // A file is taken as it is, whatever it's called
+    let other = write(&dir, "other.txt", "");
+    assert_eq!(golden::find(std::slice::from_ref(&other)).unwrap(), [other]);
+
+    let e = golden::find(&[dir.join("nope")]).unwrap_err();
+    assert!(e.to_string().contains("CAN'T FIND"), "{}", e);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,123 @@
+use std::{cell::RefCell, rc::Rc};
+
+use nooblang_rust::{
+    eval::{Buffer, Callback, Io},
+    vm, Errors, Eval, Reader,
+};
+
+// Runs the program on both engines with the Io each one is given
+fn both<'a>(source: &str, mut io: impl FnMut() -> Box<dyn Io + 'a>) -> [Result<(), String>; 2] {
+    let mut reader = Reader::from(source);
+    let ast = nooblang_rust::parse(&mut reader).unwrap();
+
+    let mut eval = Eval::new(&ast, &reader);
+    eval.set_io(io());
+    let from_eval = eval.run_prog().map(|_| ()).map_err(|e| e.to_string());
+
+    let chunk = vm::compile(&ast);
+    let mut vm = vm::Vm::new(&chunk, &ast, &reader);
+    vm.set_io(io());
+    [from_eval, vm.run_prog().map(|_| ()).map_err(|e| e.to_string())]
+}
+
+// What the program wrote, given the input
+fn output(source: &str, input: &str) -> [String; 2] {
+    let mut reader = Reader::from(source);
+    let ast = nooblang_rust::parse(&mut reader).unwrap();
+
+    let mut io = Buffer::new(input);
+    let mut eval = Eval::new(&ast, &reader);
+    eval.set_io(Box::new(&mut io));
+    eval.run_prog().unwrap();
+    drop(eval);
+    let from_eval = io.output().to_string();
+
+    let chunk = vm::compile(&ast);
+    let mut io = Buffer::new(input);
+    let mut vm = vm::Vm::new(&chunk, &ast, &reader);
+    vm.set_io(Box::new(&mut io));
+    vm.run_prog().unwrap();
+    drop(vm);
+    [from_eval, io.output().to_string()]
+}
+
+#[test]
+fn buffer_reads_a_line_at_a_time() {
+    let mut io = Buffer::new("one\ntwo\r\n\nfour");
+    assert_eq!(io.read_line().unwrap(), "one");
+    assert_eq!(io.read_line().unwrap(), "two");
+    assert_eq!(io.read_line().unwrap(), "");
+    assert_eq!(io.read_line().unwrap(), "four");
+    // Empty once there's no more, however many times it's asked
+    assert_eq!(io.read_line().unwrap(), "");
+    assert_eq!(io.read_line().unwrap(), "");
+    assert_eq!(io.output(), "");
+}
+
+#[test]
+fn buffer_keeps_what_was_written() {
+    let mut io = Buffer::new("");
+    io.write_line("a").unwrap();
+    io.write_line("").unwrap();
+    io.write_line("b\nc").unwrap();
+    assert_eq!(io.output(), "a\n\nb\nc\n");
+}
+
+#[test]
+fn programs_read_and_write_through_it() {
+    let source = "name be read\nn be num read\nwrite \"hi \" plus name\nwrite n times 2\nwrite read";
+    for out in output(source, "nooblet\n21\n") {
+        assert_eq!(out, "hi nooblet\n42\n\n");
+    }
+    // Every kind of value is written as text
+    let source = "write yes\nwrite 1 over 3\nwrite 1 dot 50\nwrite \"\"\"\na\nb\"\"\"";
+    for out in output(source, "") {
+        assert_eq!(out, "yes\n1/3\n1.5\na\nb\n");
+    }
+}
+
+#[test]
+fn callbacks_get_every_write_and_give_every_read() {
+    let written = Rc::new(RefCell::new(Vec::new()));
+    let reads = Rc::new(RefCell::new(0));
+    let source = "write read plus read\nwrite \"done\"";
+    let results = both(source, || {
+        let (written, reads) = (written.clone(), reads.clone());
+        Box::new(Callback::new(
+            move |text: &str| written.borrow_mut().push(text.to_string()),
+            move || {
+                *reads.borrow_mut() += 1;
+                format!("r{}", reads.borrow())
+            },
+        ))
+    });
+    assert_eq!(results, [Ok(()), Ok(())]);
+    assert_eq!(*written.borrow(), ["r1r2", "done", "r3r4", "done"]);
+}
+
+// Fails to write after the first line
+struct Broken(usize);
+
+impl Io for Broken {
+    fn write_line(&mut self, _text: &str) -> Result<(), Errors> {
+        self.0 += 1;
+        match self.0 {
+            1 => Ok(()),
+            _ => Err(Errors::IOError),
+        }
+    }
+
+    fn read_line(&mut self) -> Result<String, Errors> {
+        Err(Errors::IOError)
+    }
+}
+
+#[test]
+fn io_errors_stop_the_program() {
+    for result in both("write 1\nwrite 2\nwrite 3", || Box::new(Broken(0))) {
+        assert_eq!(result, Err(Errors::IOError.to_string()));
+    }
+    for result in both("x be read", || Box::new(Broken(0))) {
+        assert_eq!(result, Err(Errors::IOError.to_string()));
+    }
+}
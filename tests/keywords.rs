@@ -0,0 +1,23 @@
+use nooblang_rust::Reader;
+
+fn error(source: &str) -> String {
+    let mut reader = Reader::from(source);
+    nooblang_rust::parse(&mut reader).unwrap_err().to_string()
+}
+
+#[test]
+fn newer_keywords_as_names_say_so() {
+    assert!(error("min be 3").contains("MIN IS A KEYWORD NOW"));
+    assert!(error("x be 1\nwrite x plus length").contains("LENGTH IS A KEYWORD NOW"));
+    assert!(error("floor be 2").contains("FLOOR IS A KEYWORD NOW"));
+    assert!(error("label be 2").contains("LABEL IS A KEYWORD NOW"));
+}
+
+#[test]
+fn keywords_used_as_keywords_still_parse() {
+    for source in ["write length of \"ab\"", "write floor neg 3", "write code of letter 1 of \"A\""] {
+        let mut reader = Reader::from(source);
+        assert!(nooblang_rust::parse(&mut reader).is_ok(), "{}", source);
+    }
+    assert!(error("write length \"ab\"").contains("NEED OF"));
+}
@@ -0,0 +1,112 @@
+use nooblang_rust::{eval::Buffer, lexer::Lexer, parser::Parser, vm, Errors, Eval, Reader};
+
+// What each engine wrote, or the error it stopped with
+fn both(source: &str) -> [Result<String, String>; 2] {
+    let mut reader = Reader::from(source);
+    let ast = nooblang_rust::parse(&mut reader).unwrap();
+    let finish = |result: Result<_, Errors>, io: Buffer| match result {
+        Ok(_) => Ok(io.output().to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+
+    let mut io = Buffer::new("");
+    let mut eval = Eval::new(&ast, &reader);
+    eval.set_io(Box::new(&mut io));
+    let result = eval.run_prog();
+    drop(eval);
+    let eval = finish(result, io);
+
+    let chunk = vm::compile(&ast);
+    let mut io = Buffer::new("");
+    let mut vm = vm::Vm::new(&chunk, &ast, &reader);
+    vm.set_io(Box::new(&mut io));
+    let result = vm.run_prog();
+    drop(vm);
+    [eval, finish(result, io)]
+}
+
+fn parse_errors(source: &str) -> Vec<String> {
+    let mut reader = Reader::from(source);
+    match nooblang_rust::parse(&mut reader) {
+        Ok(_) => Vec::new(),
+        Err(Errors::Multiple(errors)) => errors.iter().map(|e| e.to_string()).collect(),
+        Err(e) => vec![e.to_string()],
+    }
+}
+
+#[test]
+fn labels_are_their_line_numbers() {
+    let source = "label start write 1\n\nlabel finish write 2\nlabel alone";
+    let mut reader = Reader::from(source);
+    let (tokens, errors) = Lexer::new(&mut reader).lex_all();
+    assert!(errors.is_empty());
+    let mut parser = Parser::new(&tokens, &reader);
+    let (prog, errors) = parser.parse_all();
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(parser.labels().get("start"), Some(&1));
+    assert_eq!(parser.labels().get("finish"), Some(&3));
+    // A line with only a label is still a line
+    assert_eq!(parser.labels().get("alone"), Some(&4));
+    assert_eq!(prog.len(), 4);
+}
+
+#[test]
+fn run_goes_to_labels() {
+    // Used before the line they're on, as a range, and with an offset
+    let source = "run shout\n\
+                  run first to last\n\
+                  end\n\
+                  label first write \"a\"\n\
+                  label last write \"b\"\n\
+                  label shout write \"!\"";
+    for result in both(source) {
+        assert_eq!(result.unwrap(), "!\na\nb\n");
+    }
+    let source = "run table plus 1\nend\nlabel table write \"zero\"\nwrite \"one\"";
+    for result in both(source) {
+        assert_eq!(result.unwrap(), "one\n");
+    }
+    let source =
+        "i be 0\nlabel next i be i plus 1\nlabel again run next to again if i below 3\nwrite i";
+    for result in both(source) {
+        assert_eq!(result.unwrap(), "3\n");
+    }
+}
+
+#[test]
+fn labels_are_only_line_numbers_in_run() {
+    // Anywhere else it's a name like any other
+    for result in both("label top write 1\ntop be 5\nwrite top\nrun top if top is 0") {
+        assert_eq!(result.unwrap(), "1\n5\n");
+    }
+    for result in both("label top write 1\nwrite top") {
+        assert!(result.unwrap_err().contains("VARIABLE top NOT FOUND"));
+    }
+}
+
+#[test]
+fn unknown_labels_are_errors() {
+    for result in both("write 1\nrun nowhere") {
+        let e = result.unwrap_err();
+        assert!(e.contains("VARIABLE nowhere NOT FOUND"), "{}", e);
+        assert!(e.contains("IN LINE: 2"), "{}", e);
+    }
+}
+
+#[test]
+fn labels_used_twice_are_errors() {
+    let errors = parse_errors("label a write 1\nlabel b write 2\nlabel a write 3\nlabel a write 4");
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].contains("LABEL a USED TWICE"));
+    assert!(errors[0].contains("IN LINE: 3"));
+    assert!(errors[1].contains("IN LINE: 4"));
+}
+
+#[test]
+fn labels_need_names() {
+    for source in ["label", "label 5 write 1", "label \"a\" write 1", "write 1 label a"] {
+        let errors = parse_errors(source);
+        assert_eq!(errors.len(), 1, "{:?}: {:?}", source, errors);
+    }
+    assert!(parse_errors("label 5 write 1")[0].contains("NEED LABEL NAME"));
+}
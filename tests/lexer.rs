@@ -0,0 +1,75 @@
+use std::{sync::mpsc, thread, time::Duration};
+
+use nooblang_rust::{lexer::Lexer, token::TokenType, Reader};
+
+// Token types and error messages, failing instead of hanging if the lexer
+// gets stuck
+fn lex(source: &str) -> (Vec<TokenType>, Vec<String>) {
+    let source = source.to_string();
+    let (send, receive) = mpsc::channel();
+    thread::spawn(move || {
+        let mut reader = Reader::from(source.as_str());
+        let (tokens, errors) = Lexer::new(&mut reader).lex_all();
+        let types = tokens.into_iter().map(|t| t.token_type).collect();
+        let errors = errors.iter().map(|e| e.to_string()).collect();
+        let _ = send.send((types, errors));
+    });
+    receive
+        .recv_timeout(Duration::from_secs(5))
+        .expect("the lexer never finished")
+}
+
+fn text(source: &str) -> String {
+    let (types, errors) = lex(source);
+    assert!(errors.is_empty(), "{:?}", errors);
+    match &types[0] {
+        TokenType::String(s) => s.clone(),
+        other => panic!("not text: {:?}", other),
+    }
+}
+
+#[test]
+fn escapes() {
+    assert_eq!(text(r#""\quote\hi\quote\""#), "\"hi\"");
+    assert_eq!(text(r#""a\newline\b\tab\c\backslash\""#), "a\nb\tc\\");
+    assert_eq!(text(r#""\unicode 1F600\""#), "\u{1F600}");
+
+    let (_, errors) = lex(r#""\nope\""#);
+    assert!(errors[0].contains("WEIRD ESCAPE: \\nope\\"));
+    let (_, errors) = lex(r#""\unicode D800\""#);
+    assert!(errors[0].contains("INVALID UNICODE: D800"));
+    let (_, errors) = lex(r#""\quote"#);
+    assert!(errors[0].contains("ESCAPE NEVER ENDS"));
+}
+
+#[test]
+fn multiline_text() {
+    assert_eq!(text("\"\"\"\nroses\nviolets\"\"\""), "roses\nviolets");
+    assert_eq!(text("\"\"\"one line\"\"\""), "one line");
+
+    let (_, errors) = lex("\"\"\"\nnever ends");
+    assert!(errors[0].contains("MISSING QUOTES"));
+    let (_, errors) = lex("\"one line");
+    assert!(errors[0].contains("MISSING QUOTE"));
+}
+
+#[test]
+fn crlf_line_endings() {
+    let (types, errors) = lex("write 1\r\nwrite 2\r\n");
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(types.iter().filter(|t| **t == TokenType::Lf).count(), 2);
+    assert_eq!(text("\"\"\"\r\na\r\nb\"\"\""), "a\nb");
+}
+
+#[test]
+fn lone_carriage_return_is_an_error() {
+    let (_, errors) = lex("write 1\rwrite 2\n");
+    assert!(errors[0].contains("CARRIAGE WITHOUT RETURN"));
+
+    // Used to never finish
+    let (types, errors) = lex("x be \"\"\"\ra\rb\"\"\"\nwrite x\n");
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("CARRIAGE WITHOUT RETURN"));
+    // The line after is still read
+    assert!(types.contains(&TokenType::Write));
+}
@@ -0,0 +1,77 @@
+use nooblang_rust::{
+    eval::{Buffer, Hook, Limits},
+    Errors, Eval, Reader,
+};
+
+// What the program wrote and how it ended, under the limits
+fn run(source: &str, limits: Limits) -> (String, Result<(), Errors>) {
+    let mut reader = Reader::from(source);
+    let ast = nooblang_rust::parse(&mut reader).unwrap();
+    let mut io = Buffer::new("");
+    let mut eval = Eval::new(&ast, &reader);
+    eval.set_io(Box::new(&mut io));
+    eval.set_limits(limits);
+    let result = eval.run_prog().map(|_| ());
+    drop(eval);
+    (io.output().to_string(), result)
+}
+
+fn depth(max: usize) -> Limits {
+    Limits {
+        depth: Some(max),
+        ..Limits::default()
+    }
+}
+
+// Every 'run' is the last line of the lines it's in, so each one is
+// finished by the time the next starts
+const LOOP: &str = "i be 1\nwrite i\ni be i plus 1\nrun 2 to 4 if i atmost 10";
+
+#[test]
+fn loop_longer_than_the_depth_passes() {
+    let (output, result) = run(LOOP, depth(3));
+    assert!(result.is_ok(), "{:?}", result);
+    assert_eq!(output.lines().count(), 10);
+}
+
+#[test]
+fn runs_inside_unfinished_runs_hit_the_depth() {
+    // Line 3 runs lines 2 to 4 and comes back for line 4, over and over
+    let source = "i be 0\ni be i plus 1\nrun 2 to 4 if i below 10\nwrite i";
+    let (_, result) = run(source, depth(3));
+    match result {
+        Err(e) => assert!(e.to_string().contains("TOO MANY RUNS INSIDE RUNS")),
+        Ok(_) => panic!("should run out of depth"),
+    }
+    let (output, result) = run(source, depth(20));
+    assert!(result.is_ok(), "{:?}", result);
+    assert_eq!(output, "10\n".repeat(10));
+}
+
+// The most 'run's there ever were on the stack
+struct Deepest(usize);
+
+impl Hook for Deepest {
+    fn line(&mut self, eval: &mut Eval, _line: usize) -> Result<(), Errors> {
+        self.0 = self.0.max(eval.call_stack().len());
+        Ok(())
+    }
+}
+
+#[test]
+fn long_loop_keeps_the_stack_small() {
+    let source = "i be 1\ni be i plus 1\nrun 2 to 3 if i below 100000\nwrite i";
+    let mut reader = Reader::from(source);
+    let ast = nooblang_rust::parse(&mut reader).unwrap();
+    let mut io = Buffer::new("");
+    let mut deepest = Deepest(0);
+    let mut eval = Eval::new(&ast, &reader);
+    eval.set_io(Box::new(&mut io));
+    eval.set_hook(Box::new(&mut deepest));
+    eval.set_limits(depth(2));
+    let result = eval.run_prog();
+    drop(eval);
+    assert!(result.is_ok(), "{:?}", result);
+    assert_eq!(io.output(), "100000\n");
+    assert!(deepest.0 <= 2, "{} 'run's on the stack", deepest.0);
+}
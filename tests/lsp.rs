@@ -0,0 +1,119 @@
+use nooblang_rust::{
+    json::{self, Json, Message},
+    lsp,
+};
+
+// Sends the messages to the server and gives back everything it answered
+fn talk(messages: Vec<Json>) -> Vec<Json> {
+    let mut input = Vec::new();
+    for message in &messages {
+        json::write_message(&mut input, message).unwrap();
+    }
+    talk_raw(&input)
+}
+
+fn talk_raw(input: &[u8]) -> Vec<Json> {
+    let mut output = Vec::new();
+    lsp::serve(input, &mut output).unwrap();
+
+    let mut answers = Vec::new();
+    let mut output = &output[..];
+    while let Some(Message::Json(answer)) = json::read_message(&mut output).unwrap() {
+        answers.push(answer);
+    }
+    answers
+}
+
+fn open(text: &str) -> Json {
+    Json::object(vec![
+        ("jsonrpc", "2.0".into()),
+        ("method", "textDocument/didOpen".into()),
+        (
+            "params",
+            Json::object(vec![(
+                "textDocument",
+                Json::object(vec![("uri", "file:///a.noob".into()), ("text", text.into())]),
+            )]),
+        ),
+    ])
+}
+
+fn hover(line: usize, character: usize) -> Json {
+    Json::object(vec![
+        ("jsonrpc", "2.0".into()),
+        ("id", 1.into()),
+        ("method", "textDocument/hover".into()),
+        (
+            "params",
+            Json::object(vec![
+                (
+                    "textDocument",
+                    Json::object(vec![("uri", "file:///a.noob".into())]),
+                ),
+                (
+                    "position",
+                    Json::object(vec![("line", line.into()), ("character", character.into())]),
+                ),
+            ]),
+        ),
+    ])
+}
+
+fn hover_value(text: &str) -> String {
+    let answers = talk(vec![open(text), hover(1, 0)]);
+    let answer = answers.iter().find(|a| *a.get("id") == 1.into()).unwrap();
+    let value = answer.get("result").get("contents").get("value");
+    value.as_str().unwrap().to_string()
+}
+
+#[test]
+fn hover_on_a_huge_target_only_shows_lines_that_are_there() {
+    let value = hover_value("write 1\nrun 1 to 99999999999\n");
+    assert!(value.starts_with("runs lines 1 to 99999999999"));
+    assert!(value.contains("   1 | write 1"));
+    assert!(value.contains("   2 | run 1 to 99999999999"));
+    assert!(!value.contains("   4 |"));
+}
+
+#[test]
+fn hover_past_the_end_has_no_preview() {
+    let value = hover_value("write 1\nrun 99999999999\n");
+    assert_eq!(value, "runs line 99999999999");
+}
+
+#[test]
+fn hover_on_a_long_range_is_cut_short() {
+    let value = hover_value(&"write 1\nrun 1 to 40\n".repeat(20));
+    assert!(value.contains("  20 |"));
+    assert!(!value.contains("  21 |"));
+    assert!(value.ends_with("     ...\n```"));
+}
+
+#[test]
+fn bad_messages_get_a_parse_error_and_the_session_goes_on() {
+    let mut input = Vec::new();
+    // Not JSON, then not UTF-8, then no length at all, then a garbled one
+    input.extend_from_slice(b"Content-Length: 5\r\n\r\n{oops");
+    input.extend_from_slice(b"Content-Length: 2\r\n\r\n\xff\xfe");
+    input.extend_from_slice(b"Content-Type: x\r\n\r\n");
+    input.extend_from_slice(b"Content-Length: lots\r\n\r\n{}");
+    json::write_message(&mut input, &open("write 1\nrun 1")).unwrap();
+    json::write_message(&mut input, &hover(1, 0)).unwrap();
+
+    let answers = talk_raw(&input);
+    let errors: Vec<_> = answers
+        .iter()
+        .filter(|a| *a.get("error").get("code") == Json::Number(-32700.0))
+        .collect();
+    assert_eq!(errors.len(), 4);
+    assert!(errors.iter().all(|e| *e.get("id") == Json::Null));
+    let hover = answers.iter().find(|a| *a.get("id") == 1.into()).unwrap();
+    let value = hover.get("result").get("contents").get("value");
+    assert_eq!(value.as_str(), Some("runs line 1\n```\n   1 | write 1\n```"));
+}
+
+#[test]
+fn hanging_up_in_the_middle_of_a_message_is_an_error() {
+    let mut output = Vec::new();
+    assert!(lsp::serve(&b"Content-Length: 50\r\n\r\n{}"[..], &mut output).is_err());
+}
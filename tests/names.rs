@@ -0,0 +1,75 @@
+use nooblang_rust::{eval::Buffer, lexer::Lexer, token::TokenType, Eval, Reader};
+
+fn tokens(source: &str) -> Vec<TokenType> {
+    let mut reader = Reader::from(source);
+    let (tokens, errors) = Lexer::new(&mut reader).lex_all();
+    assert!(errors.is_empty(), "{:?}", errors);
+    tokens.into_iter().map(|t| t.token_type).collect()
+}
+
+fn id(name: &str) -> TokenType {
+    TokenType::Id(name.to_string())
+}
+
+fn output(source: &str) -> String {
+    let mut reader = Reader::from(source);
+    let ast = nooblang_rust::parse(&mut reader).unwrap();
+    let mut io = Buffer::new("");
+    let mut eval = Eval::new(&ast, &reader);
+    eval.set_io(Box::new(&mut io));
+    eval.run_prog().unwrap();
+    drop(eval);
+    io.output().to_string()
+}
+
+#[test]
+fn names_can_have_digits_after_the_first_letter() {
+    assert_eq!(tokens("x1")[0], id("x1"));
+    assert_eq!(tokens("row2col3")[0], id("row2col3"));
+    assert_eq!(output("x1 be 2\nx2 be 3\nwrite x1 times x2"), "6\n");
+
+    // A number first is still a number
+    assert_eq!(tokens("1x")[..2], [TokenType::Number(1), id("x")]);
+    let mut reader = Reader::from("1x be 2");
+    assert!(nooblang_rust::parse(&mut reader).is_err());
+}
+
+#[test]
+fn names_can_be_in_any_language() {
+    assert_eq!(tokens("café")[0], id("café"));
+    assert_eq!(tokens("日本")[0], id("日本"));
+    assert_eq!(tokens("Ωmega2")[0], id("Ωmega2"));
+    assert_eq!(output("é be 1\n日本 be 2\nwrite é plus 日本"), "3\n");
+}
+
+#[test]
+fn keywords_are_only_whole_words() {
+    // Starting with a keyword doesn't make it one
+    assert_eq!(tokens("writer")[0], id("writer"));
+    assert_eq!(tokens("be2")[0], id("be2"));
+    assert_eq!(tokens("ifs")[0], id("ifs"));
+    assert_eq!(output("writer be 1\nbe2 be 2\nwrite writer plus be2"), "3\n");
+    // Keywords are lowercase
+    assert_eq!(tokens("Write")[0], id("Write"));
+    assert_eq!(tokens("write")[0], TokenType::Write);
+}
+
+#[test]
+fn note2_is_a_name_and_not_a_note() {
+    assert_eq!(tokens("note2 be 3")[..3], [id("note2"), TokenType::Be, TokenType::Number(3)]);
+    assert_eq!(output("note2 be 3\nwrite note2\nnote write 4\nwrite 5"), "3\n5\n");
+    // The rest of the line after 'note' is skipped, whatever is in it
+    assert_eq!(tokens("note x1 + \" é\n"), [TokenType::Lf, TokenType::Eof]);
+    // Even on the last line without a newline
+    assert_eq!(tokens("note x1 + \" é"), [TokenType::Eof]);
+    assert_eq!(output("write 1\nnote the end"), "1\n");
+}
+
+#[test]
+fn other_symbols_are_not_part_of_names() {
+    for source in ["a_b be 1", "a-b be 1", "a.b be 1", "a$ be 1"] {
+        let mut reader = Reader::from(source);
+        let e = nooblang_rust::parse(&mut reader).unwrap_err();
+        assert!(e.to_string().contains("WEIRD SYMBOL"), "{:?}: {}", source, e);
+    }
+}
@@ -0,0 +1,122 @@
+use nooblang_rust::{
+    eval::{Buffer, Hook},
+    lexer::Span,
+    vm, Errors, Eval, Reader,
+};
+
+// What each engine wrote, or the error it stopped with
+fn both(source: &str) -> [Result<String, String>; 2] {
+    let mut reader = Reader::from(source);
+    let ast = nooblang_rust::parse(&mut reader).unwrap();
+    let finish = |result: Result<_, Errors>, io: Buffer| match result {
+        Ok(_) => Ok(io.output().to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+
+    let mut io = Buffer::new("");
+    let mut eval = Eval::new(&ast, &reader);
+    eval.set_io(Box::new(&mut io));
+    let result = eval.run_prog();
+    drop(eval);
+    let eval = finish(result, io);
+
+    let chunk = vm::compile(&ast);
+    let mut io = Buffer::new("");
+    let mut vm = vm::Vm::new(&chunk, &ast, &reader);
+    vm.set_io(Box::new(&mut io));
+    let result = vm.run_prog();
+    drop(vm);
+    [eval, finish(result, io)]
+}
+
+fn writes(source: &str, expected: &str) {
+    for result in both(source) {
+        assert_eq!(result.as_deref(), Ok(expected), "{:?}", source);
+    }
+}
+
+const SIZES: &str = "write \"big\" if x above 10 otherwise write \"mid\" if x above 5 otherwise write \"small\"";
+
+#[test]
+fn the_first_case_that_holds_runs() {
+    writes(&format!("x be 20\n{}", SIZES), "big\n");
+    writes(&format!("x be 7\n{}", SIZES), "mid\n");
+    writes(&format!("x be 1\n{}", SIZES), "small\n");
+}
+
+#[test]
+fn without_a_last_case_nothing_might_run() {
+    writes("write 1 if no otherwise write 2 if no\nwrite 3", "3\n");
+    writes("x be 1 if no otherwise x be 2\nwrite x", "2\n");
+}
+
+#[test]
+fn conditions_are_choices_like_for_if() {
+    writes("write 1 if 0 otherwise write 2", "2\n");
+    writes("write 1 if \"\" otherwise write 2", "2\n");
+    writes("write 1 if 5 otherwise write 2", "1\n");
+}
+
+#[test]
+fn later_cases_are_only_looked_at_when_needed() {
+    // 'x' doesn't exist, but that case is never reached
+    writes("write 1 if yes otherwise write 2 if x", "1\n");
+    for result in both("write 1 if no otherwise write 2 if x") {
+        assert!(result.unwrap_err().contains("VARIABLE x NOT FOUND"));
+    }
+}
+
+#[test]
+fn cases_can_run_lines() {
+    let source = "x be 0\n\
+                  run 5 if x is 1 otherwise run 6 to 7\n\
+                  write \"back\"\n\
+                  end\n\
+                  write \"one\"\n\
+                  write \"not one\"\n\
+                  write \"really\"";
+    writes(source, "not one\nreally\nback\n");
+}
+
+// Every condition that was looked at and how it went
+struct Switches(Vec<(usize, bool)>);
+
+impl Hook for Switches {
+    fn line(&mut self, _eval: &mut Eval, _line: usize) -> Result<(), Errors> {
+        Ok(())
+    }
+
+    fn switch(&mut self, cond: Span, yes: bool) {
+        let Span(start, _) = cond;
+        self.0.push((start.1, yes));
+    }
+}
+
+#[test]
+fn each_condition_looked_at_goes_to_the_hook() {
+    let source = format!("x be 7\n{}", SIZES);
+    let mut reader = Reader::from(source.as_str());
+    let ast = nooblang_rust::parse(&mut reader).unwrap();
+    let mut switches = Switches(Vec::new());
+    let mut io = Buffer::new("");
+    let mut eval = Eval::new(&ast, &reader);
+    eval.set_io(Box::new(&mut io));
+    eval.set_hook(Box::new(&mut switches));
+    eval.run_prog().unwrap();
+    drop(eval);
+    // The columns of 'x above 10' and 'x above 5'
+    assert_eq!(switches.0, [(16, false), (52, true)]);
+}
+
+#[test]
+fn otherwise_needs_an_if_before_it() {
+    for (source, msg) in [
+        ("write 1 otherwise write 2", "WHY THIS HERE"),
+        ("write 1 if yes otherwise", "NEED VALUE"),
+        ("otherwise write 1", "OTHERWISE IS A KEYWORD NOW"),
+    ] {
+        let mut reader = Reader::from(source);
+        let e = nooblang_rust::parse(&mut reader).unwrap_err();
+        assert!(e.to_string().contains(msg), "{:?}: {}", source, e);
+    }
+}
@@ -0,0 +1,74 @@
+use std::path::PathBuf;
+
+use nooblang_rust::{eval::Buffer, golden, vm, Errors, Eval, Reader};
+
+// What the program wrote, then the error it stopped with if there was one
+fn outcome(result: Result<nooblang_rust::Value, Errors>, io: Buffer) -> (String, String) {
+    let error = result.err().map(|e| e.to_string()).unwrap_or_default();
+    (io.output().to_string(), error)
+}
+
+fn both(source: &str, input: &str) -> [(String, String); 2] {
+    let mut reader = Reader::from(source);
+    let ast = nooblang_rust::parse(&mut reader).unwrap();
+
+    let mut io = Buffer::new(input);
+    let mut eval = Eval::new(&ast, &reader);
+    eval.set_io(Box::new(&mut io));
+    let result = eval.run_prog();
+    drop(eval);
+    let from_eval = outcome(result, io);
+
+    let chunk = vm::compile(&ast);
+    let mut io = Buffer::new(input);
+    let mut vm = vm::Vm::new(&chunk, &ast, &reader);
+    vm.set_io(Box::new(&mut io));
+    let result = vm.run_prog();
+    drop(vm);
+    [from_eval, outcome(result, io)]
+}
+
+#[test]
+fn examples_do_the_same_on_both_engines() {
+    let paths = golden::find(&[PathBuf::from("examples")]).unwrap();
+    assert!(!paths.is_empty());
+    for path in &paths {
+        let from_eval = golden::run(path, false, None).unwrap();
+        let from_vm = golden::run(path, true, None).unwrap();
+        assert_eq!(from_eval, from_vm, "{}", path.display());
+    }
+}
+
+#[test]
+fn programs_do_the_same_on_both_engines() {
+    let programs = [
+        // Arithmetic of every kind of number
+        "write 7 over 2\nwrite 1 dot 5 plus 1 over 2\nwrite 2 tothe 10\nwrite 7 div 2\nwrite neg 7 mod 3",
+        "write 170141183460469231731687303715884105727 plus 1",
+        "write sqrt 2\nwrite round 5 over 2\nwrite floor neg 1 dot 5\nwrite abs neg 3\nwrite 3 min 4 max 2",
+        // Text
+        "s be \"hello\"\nwrite length of s\nwrite letter 2 of s\nwrite part 2 to 4 of s\nwrite upper s",
+        "write find \"l\" in \"hello\"\nwrite code of \"A\"\nwrite character 66\nwrite \"a\" plus \"b\"",
+        // Choices and comparisons
+        "write 1 below 2 and 2 above 3\nwrite not yes or no\nwrite \"a\" is \"a\"\nwrite 1 over 2 is 2 over 4",
+        // 'run's: loops, lines that come back, repeat, labels
+        "i be 1\nwrite i\ni be i plus 1\nrun 2 to 4 if i atmost 5",
+        "i be 0\ni be i plus 1\nrun 2 to 4 if i below 3\nwrite i",
+        "write \"a\"\nrun 1 repeat 3\nrepeat 2\nrun 1 repeat 0",
+        "label top write \"x\"\nn be 0\nlabel again n be n plus 1\nrun again if n below 3\nwrite n",
+        // otherwise
+        "x be 7\nwrite \"big\" if x above 10 otherwise write \"mid\" if x above 5 otherwise write \"small\"",
+        // Reading
+        "a be read\nb be read\nwrite a plus b\nwrite read",
+        // Ending early and stopping with an error after writing
+        "write 1\nend\nwrite 2",
+        "write 1\nwrite x",
+        "write 1\nwrite 1 over 0",
+        "write 1\nrun 1 to 9",
+        "write 1\nrepeat neg 1",
+    ];
+    for source in programs {
+        let [from_eval, from_vm] = both(source, "3\n4\nlast\n");
+        assert_eq!(from_eval, from_vm, "{:?}", source);
+    }
+}
@@ -0,0 +1,105 @@
+use nooblang_rust::{eval::Buffer, profiler::Profiler, Eval, Reader};
+
+// Lines 2 to 4 go around 3 times
+const LOOP: &str = "i be 1\nwrite i\ni be i plus 1\nrun 2 to 4 if i atmost 3\nwrite \"done\"";
+
+fn profile(source: &str) -> (Profiler, Reader) {
+    let mut reader = Reader::from(source);
+    let ast = nooblang_rust::parse(&mut reader).unwrap();
+    let mut profiler = Profiler::new(ast.len());
+    let mut io = Buffer::new("");
+    let mut eval = Eval::new(&ast, &reader);
+    eval.set_io(Box::new(&mut io));
+    eval.set_hook(Box::new(&mut profiler));
+    eval.run_prog().unwrap();
+    drop(eval);
+    profiler.finish();
+    assert_eq!(io.output(), "1\n2\n3\ndone\n");
+    (profiler, reader)
+}
+
+#[test]
+fn counts_lines_ifs_and_runs() {
+    let (profiler, _) = profile(LOOP);
+    let hits: Vec<_> = profiler.lines().iter().map(|stats| stats.hits).collect();
+    assert_eq!(hits, [1, 3, 3, 3, 1]);
+    assert_eq!((profiler.lines()[3].yes, profiler.lines()[3].no), (2, 1));
+    assert_eq!(profiler.runs().get(&(2, 4)), Some(&2));
+}
+
+#[test]
+fn listing() {
+    let (profiler, reader) = profile(LOOP);
+    let listing = profiler.listing(&reader);
+    let lines: Vec<_> = listing.lines().collect();
+    assert!(lines[0].starts_with(" LINE      HITS        TIME  SOURCE"));
+    assert!(lines[2].starts_with("    2         3 "));
+    assert!(lines[2].ends_with("  write i"));
+    assert!(lines[4].ends_with("  run 2 to 4 if i atmost 3    <- IF: 2 YES, 1 NO"));
+    assert!(lines[6].starts_with("TOTAL "));
+    assert_eq!(&lines[7..], ["", "RUNS", "    2 TO 4             2 TIMES"]);
+}
+
+#[test]
+fn to_json() {
+    let (profiler, _) = profile(LOOP);
+    let json = profiler.to_json();
+    let lines = json.get("lines").as_array();
+    assert_eq!(lines.len(), 5);
+    let line = &lines[3];
+    assert_eq!(line.get("line").as_usize(), Some(4));
+    assert_eq!(line.get("hits").as_usize(), Some(3));
+    assert_eq!(line.get("yes").as_usize(), Some(2));
+    assert_eq!(line.get("no").as_usize(), Some(1));
+    assert!(line.get("nanos").as_f64().is_some());
+
+    let runs = json.get("runs").as_array();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].get("from").as_usize(), Some(2));
+    assert_eq!(runs[0].get("to").as_usize(), Some(4));
+    assert_eq!(runs[0].get("times").as_usize(), Some(2));
+}
+
+#[test]
+fn collapsed() {
+    let (profiler, _) = profile(LOOP);
+    // Without the times, which change from run to run
+    let mut stacks: Vec<_> = profiler
+        .collapsed()
+        .lines()
+        .map(|line| line.rsplit_once(' ').unwrap().0.to_string())
+        .collect();
+    stacks.sort();
+    assert_eq!(
+        stacks,
+        [
+            "lines 1-5;line 1",
+            "lines 1-5;line 2",
+            "lines 1-5;line 3",
+            "lines 1-5;line 4",
+            "lines 1-5;line 5",
+            "lines 1-5;lines 2-4;line 2",
+            "lines 1-5;lines 2-4;line 3",
+            "lines 1-5;lines 2-4;line 4",
+        ]
+    );
+}
+
+#[test]
+fn deep_stacks_are_cut_short() {
+    // Every 'run' comes back for line 4, so they all stay on the stack
+    let source = "i be 0\ni be i plus 1\nrun 2 to 4 if i below 100\ni be i";
+    let mut reader = Reader::from(source);
+    let ast = nooblang_rust::parse(&mut reader).unwrap();
+    let mut profiler = Profiler::new(ast.len());
+    let mut eval = Eval::new(&ast, &reader);
+    eval.set_hook(Box::new(&mut profiler));
+    eval.run_prog().unwrap();
+    drop(eval);
+    profiler.finish();
+    let collapsed = profiler.collapsed();
+    let deepest = collapsed.lines().map(|line| line.split(';').count()).max();
+    // '...', the innermost 64 'run's, then the line
+    assert_eq!(deepest, Some(66));
+    assert!(collapsed.lines().any(|line| line.starts_with("...;lines 2-4;")));
+}
@@ -0,0 +1,88 @@
+use nooblang_rust::{ast::Stmt, Errors, Reader};
+
+// One error on each of lines 1, 3, 4, 5 and 6, from the lexer and the parser
+const TYPOS: &str = "write 1 +\nwrite 2\nx be\n1 be 2\nwrite \"oops\nwrite 3 4\nwrite 5\n";
+
+// (line, message) of each error
+fn found(errors: &[Errors]) -> Vec<(usize, String)> {
+    errors
+        .iter()
+        .map(|e| match e {
+            Errors::SyntaxError(msg, span, ..) => (span.line(), msg.clone()),
+            e => panic!("not a syntax error: {:?}", e),
+        })
+        .collect()
+}
+
+#[test]
+fn every_line_with_a_typo_is_found() {
+    let mut reader = Reader::from(TYPOS);
+    let (_, errors) = nooblang_rust::parse_all(&mut reader);
+    let mut errors = found(&errors);
+    errors.sort();
+    let expected = [
+        (1, "WEIRD SYMBOL: +"),
+        (3, "NEED VALUE"),
+        (4, "WHERE IDENTIFIER"),
+        (5, "MISSING QUOTE"),
+        (6, "WHY THIS HERE"),
+    ];
+    assert_eq!(errors, expected.map(|(line, msg)| (line, msg.to_string())));
+}
+
+#[test]
+fn lines_with_typos_become_blank() {
+    let mut reader = Reader::from(TYPOS);
+    let (prog, _) = nooblang_rust::parse_all(&mut reader);
+    // Everything keeps its line number
+    assert_eq!(prog.len(), 7);
+    for (i, stmt) in prog.iter().enumerate() {
+        let blank = matches!(stmt, Stmt::Blank);
+        assert_eq!(blank, [1, 3, 4, 5, 6].contains(&(i + 1)), "line {}: {:?}", i + 1, stmt);
+    }
+}
+
+#[test]
+fn parse_gives_them_all_in_line_order() {
+    let mut reader = Reader::from(TYPOS);
+    let e = nooblang_rust::parse(&mut reader).unwrap_err();
+    let Errors::Multiple(errors) = &e else {
+        panic!("not all of them: {:?}", e);
+    };
+    let lines: Vec<_> = found(errors).iter().map(|(line, _)| *line).collect();
+    assert_eq!(lines, [1, 3, 4, 5, 6]);
+    assert!(e.to_string().ends_with("5 TYPOS FOUND"));
+
+    // Just the one on its own
+    let mut reader = Reader::from("write 1\nwrite\n");
+    match nooblang_rust::parse(&mut reader) {
+        Err(Errors::SyntaxError(msg, ..)) => assert_eq!(msg, "NEED VALUE"),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn one_typo_per_line() {
+    // The rest of a line is skipped after its first error
+    let mut reader = Reader::from("write 1 2 3 4\nwrite open 1 plus\nwrite 5");
+    let (prog, errors) = nooblang_rust::parse_all(&mut reader);
+    let lines: Vec<_> = found(&errors).iter().map(|(line, _)| *line).collect();
+    assert_eq!(lines, [1, 2]);
+    assert!(matches!(prog[2], Stmt::Write(..)));
+}
+
+#[test]
+fn text_that_never_ends_only_spoils_its_own_lines() {
+    let mut reader = Reader::from("x be \"\"\"\nstill text\nwrite 1 +\n");
+    let (prog, errors) = nooblang_rust::parse_all(&mut reader);
+    assert_eq!(found(&errors), [(1, "MISSING QUOTES".to_string())]);
+    assert!(prog.iter().all(|stmt| matches!(stmt, Stmt::Blank)));
+
+    let mut reader = Reader::from("x be \"\"\"\nsome\ntext\"\"\" plus\nwrite 1 +\nwrite 2");
+    let (prog, errors) = nooblang_rust::parse_all(&mut reader);
+    let mut lines: Vec<_> = found(&errors).iter().map(|(line, _)| *line).collect();
+    lines.sort();
+    assert_eq!(lines, [3, 4]);
+    assert_eq!(prog.len(), 5);
+    assert!(matches!(prog[4], Stmt::Write(..)));
+}
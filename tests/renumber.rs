@@ -0,0 +1,122 @@
+use nooblang_rust::renumber::{renumber, Edit};
+
+// The new source and what it says to check, as (line, message)
+fn apply(source: &str, edit: Edit) -> (String, Vec<(usize, String)>) {
+    let (source, notes) = renumber(source, edit).unwrap();
+    let notes = notes.into_iter().map(|note| (note.line, note.msg)).collect();
+    (source, notes)
+}
+
+const LOOP: &str = "i be 1\nwrite i\ni be i plus 1\nrun 2 to 4 if i atmost 3\nwrite \"done\"";
+
+#[test]
+fn insert_shifts_targets_after_it() {
+    let (source, notes) = apply(LOOP, Edit::Insert(3, 2));
+    assert_eq!(
+        source,
+        "i be 1\nwrite i\n\n\ni be i plus 1\nrun 2 to 6 if i atmost 3\nwrite \"done\""
+    );
+    assert!(notes.is_empty());
+
+    // Before the first line moves everything
+    let (source, _) = apply(LOOP, Edit::Insert(1, 1));
+    assert!(source.starts_with("\ni be 1\n"));
+    assert!(source.contains("run 3 to 5 if"));
+    // After the last line moves nothing
+    let (source, _) = apply(LOOP, Edit::Insert(6, 1));
+    assert_eq!(source, LOOP.to_string() + "\n");
+}
+
+#[test]
+fn delete_shifts_targets_after_it() {
+    let source = "write 1\nwrite 2\nwrite 3\nrun 3\nrun 1 to 4";
+    let (source, notes) = apply(source, Edit::Delete(2, 1));
+    assert_eq!(source, "write 1\nwrite 3\nrun 2\nrun 1 to 3");
+    assert!(notes.is_empty());
+}
+
+#[test]
+fn deleting_a_target_shrinks_the_range_and_says_so() {
+    let (source, notes) = apply(LOOP, Edit::Delete(2, 1));
+    assert_eq!(source, "i be 1\ni be i plus 1\nrun 2 to 3 if i atmost 3\nwrite \"done\"");
+    assert_eq!(notes, [(3, "'run' POINTED AT DELETED LINE 2".to_string())]);
+
+    let source = "write 1\nwrite 2\nwrite 3\nrun 1 to 3";
+    let (source, notes) = apply(source, Edit::Delete(3, 1));
+    assert_eq!(source, "write 1\nwrite 2\nrun 1 to 2");
+    assert_eq!(notes, [(3, "'run' POINTED AT DELETED LINE 3".to_string())]);
+}
+
+#[test]
+fn every_target_on_a_line_moves() {
+    let source = "x be 1\nwrite x\nrun 2 repeat 2 if x is 1 otherwise run 1 to 2";
+    let (source, _) = apply(source, Edit::Insert(2, 1));
+    assert_eq!(source, "x be 1\n\nwrite x\nrun 3 repeat 2 if x is 1 otherwise run 1 to 3");
+}
+
+#[test]
+fn labels_and_computed_targets() {
+    let source = "label top write 1\nrun top\nn be 0\nrun 1 plus n";
+    let (source, notes) = apply(source, Edit::Insert(1, 1));
+    // The label moves with its line, the sum can't be known
+    assert_eq!(source, "\nlabel top write 1\nrun top\nn be 0\nrun 1 plus n");
+    assert_eq!(notes, [(5, "COMPUTED 'run' TARGET, CHECK 1 BY HAND".to_string())]);
+}
+
+#[test]
+fn keeps_the_rest_of_the_line() {
+    // Wide letters before the number, and the spacing around it
+    let source = "write \"héllo\"\nwrite \"日本\"   if   yes otherwise run    1";
+    let (source, _) = apply(source, Edit::Insert(1, 1));
+    assert_eq!(source, "\nwrite \"héllo\"\nwrite \"日本\"   if   yes otherwise run    2");
+}
+
+#[test]
+fn edits_outside_the_program_are_errors() {
+    for edit in [
+        Edit::Insert(0, 1),
+        Edit::Insert(7, 1),
+        Edit::Insert(2, 0),
+        Edit::Delete(0, 1),
+        Edit::Delete(5, 2),
+        Edit::Delete(1, 0),
+    ] {
+        let e = renumber(LOOP, edit).unwrap_err();
+        assert!(e.to_string().contains("CAN'T CHANGE LINE"), "{:?}", edit);
+    }
+    assert!(renumber("write 1 +", Edit::Insert(1, 1)).is_err());
+}
+
+#[test]
+fn targets_past_the_end_are_left_alone() {
+    // Way too big for a usize, and just too big for a 64-bit one
+    let source = "write 1\nrun 170141183460469231731687303715884105727\nrun 2 to 18446744073709551616";
+    let (new, notes) = apply(source, Edit::Insert(1, 1));
+    assert_eq!(
+        new,
+        "\nwrite 1\nrun 170141183460469231731687303715884105727\nrun 3 to 18446744073709551616"
+    );
+    assert_eq!(
+        notes,
+        [
+            (
+                3,
+                "'run' POINTS PAST THE LAST LINE AT 170141183460469231731687303715884105727, CHECK IT BY HAND"
+                    .to_string()
+            ),
+            (
+                4,
+                "'run' POINTS PAST THE LAST LINE AT 18446744073709551616, CHECK IT BY HAND".to_string()
+            ),
+        ]
+    );
+    let (new, notes) = apply("write 1\nrun 1 to 3", Edit::Delete(1, 1));
+    assert_eq!(new, "run 1 to 3");
+    assert_eq!(
+        notes,
+        [
+            (1, "'run' POINTED AT DELETED LINE 1".to_string()),
+            (1, "'run' POINTS PAST THE LAST LINE AT 3, CHECK IT BY HAND".to_string()),
+        ]
+    );
+}
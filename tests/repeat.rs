@@ -0,0 +1,109 @@
+use nooblang_rust::{
+    eval::{Buffer, Limits},
+    vm, Errors, Eval, Reader,
+};
+
+// What each engine wrote, or the error it stopped with
+fn both(source: &str) -> [Result<String, String>; 2] {
+    let mut reader = Reader::from(source);
+    let ast = nooblang_rust::parse(&mut reader).unwrap();
+    let finish = |result: Result<_, Errors>, io: Buffer| match result {
+        Ok(_) => Ok(io.output().to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+
+    let mut io = Buffer::new("");
+    let mut eval = Eval::new(&ast, &reader);
+    eval.set_io(Box::new(&mut io));
+    let result = eval.run_prog();
+    drop(eval);
+    let eval = finish(result, io);
+
+    let chunk = vm::compile(&ast);
+    let mut io = Buffer::new("");
+    let mut vm = vm::Vm::new(&chunk, &ast, &reader);
+    vm.set_io(Box::new(&mut io));
+    let result = vm.run_prog();
+    drop(vm);
+    [eval, finish(result, io)]
+}
+
+fn writes(source: &str, expected: &str) {
+    for result in both(source) {
+        assert_eq!(result.as_deref(), Ok(expected), "{:?}", source);
+    }
+}
+
+fn fails(source: &str, msg: &str) {
+    for result in both(source) {
+        let e = result.unwrap_err();
+        assert!(e.contains(msg), "{:?}: {}", source, e);
+    }
+}
+
+#[test]
+fn run_goes_through_the_lines_that_many_times() {
+    writes("run 3 to 4 repeat 3\nend\nwrite \"a\"\nwrite \"b\"", "a\nb\na\nb\na\nb\n");
+    writes("i be 0\ni be i plus 1\nrun 2 repeat 4\nwrite i", "5\n");
+    // Computed, and once is the same as a plain 'run'
+    writes("n be 2\nwrite \"x\"\nrun 2 repeat n plus 1", "x\nx\nx\nx\n");
+    writes("write \"x\"\nrun 1 repeat 1", "x\nx\n");
+}
+
+#[test]
+fn repeat_on_its_own_goes_through_the_last_run_again() {
+    writes("write 1\nrun 1 repeat 2\nrepeat 1", "1\n1\n1\n1\n");
+    // The last one, not the first
+    writes("write 1\nwrite 2\nrun 1\nrun 2\nrepeat 2", "1\n2\n1\n2\n2\n2\n");
+    // Even a run to a label
+    writes("run hi\nrepeat 1\nend\nlabel hi write \"hi\"", "hi\nhi\n");
+}
+
+#[test]
+fn zero_times_skips_the_lines() {
+    writes("write 1\nrun 1 repeat 0\nwrite 2", "1\n2\n");
+    writes("write 1\nrun 1\nrepeat 0\nwrite 2", "1\n1\n2\n");
+}
+
+#[test]
+fn repeat_works_with_if_and_otherwise() {
+    writes("write 1\nrun 1 repeat 2 if yes", "1\n1\n1\n");
+    writes("write 1\nrun 1 repeat 2 if no", "1\n");
+    writes("write 1\nrun 1 repeat 2 if no otherwise run 1", "1\n1\n");
+}
+
+#[test]
+fn repeat_needs_a_whole_number_of_times() {
+    for times in ["neg 1", "1 over 2", "1 dot 5", "\"2\"", "yes"] {
+        fails(&format!("write 1\nrun 1 repeat {}", times), "CAN'T REPEAT DIS");
+        fails(&format!("write 1\nrun 1\nrepeat {}", times), "CAN'T REPEAT DIS");
+    }
+}
+
+#[test]
+fn repeat_needs_a_run_before_it() {
+    fails("repeat 2", "NOTHING TO REPEAT");
+    // A 'run' that hasn't happened yet doesn't count
+    fails("repeat 2\nrun 3\nwrite 1", "NOTHING TO REPEAT");
+    let mut reader = Reader::from("write 1 repeat 3");
+    let e = nooblang_rust::parse(&mut reader).unwrap_err();
+    assert!(e.to_string().contains("WHY THIS HERE"));
+}
+
+#[test]
+fn many_times_is_one_run_deep() {
+    let mut reader = Reader::from("i be 0\ni be i plus 1\nrun 2 repeat 10000\nwrite i");
+    let ast = nooblang_rust::parse(&mut reader).unwrap();
+    let mut io = Buffer::new("");
+    let mut eval = Eval::new(&ast, &reader);
+    eval.set_io(Box::new(&mut io));
+    eval.set_limits(Limits {
+        // The program itself and the one 'run'
+        depth: Some(2),
+        ..Limits::default()
+    });
+    let result = eval.run_prog();
+    drop(eval);
+    assert!(result.is_ok(), "{:?}", result);
+    assert_eq!(io.output(), "10001\n");
+}
@@ -0,0 +1,116 @@
+use nooblang_rust::{eval::Buffer, repl::Repl};
+
+// What the REPL answered, what went wrong and what the program wrote,
+// with the banner and the prompts left out
+fn session(lines: &str, input: &str) -> (Vec<String>, String, String) {
+    let mut io = Buffer::new(input);
+    let (mut output, mut errors) = (Vec::new(), Vec::new());
+    let mut repl = Repl::new();
+    repl.set_io(Box::new(&mut io));
+    repl.serve(lines.as_bytes(), &mut output, &mut errors).unwrap();
+    drop(repl);
+
+    let output = String::from_utf8(output).unwrap();
+    let answers = output
+        .lines()
+        .skip(1)
+        .map(|mut line| {
+            // Every prompt is "N> ", and answers come after the one they're for
+            while let Some((n, rest)) = line.split_once("> ") {
+                if n.is_empty() || !n.chars().all(|c| c.is_ascii_digit()) {
+                    break;
+                }
+                line = rest;
+            }
+            line.to_string()
+        })
+        .filter(|line| !line.is_empty())
+        .collect();
+    (answers, String::from_utf8(errors).unwrap(), io.output().to_string())
+}
+
+#[test]
+fn prompts_count_the_lines_of_the_program() {
+    let mut output = Vec::new();
+    let mut repl = Repl::new();
+    repl.serve(&b"x be 1\n\nwrite +\n:vars\nx be 2\n"[..], &mut output, Vec::new())
+        .unwrap();
+    // Blank lines, typos and commands don't add to it
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(output, "NOOBLANG REPL, :help FOR HELP\n1> 2> 2> 2> x be 1\n2> 3> \n");
+}
+
+#[test]
+fn expressions_show_their_value() {
+    let (answers, errors, _) = session("1 plus 2\n\"hi\"\nyes\nx be 1 over 3\nx", "");
+    assert_eq!(answers, ["3", "\"hi\"", "yes", "1/3"]);
+    assert_eq!(errors, "");
+}
+
+#[test]
+fn the_program_reads_and_writes_through_its_io() {
+    let (answers, _, written) = session("name be read\nwrite \"hi \" plus name", "nooblet\n");
+    assert!(answers.is_empty(), "{:?}", answers);
+    assert_eq!(written, "hi nooblet\n");
+}
+
+#[test]
+fn list_shows_the_program_so_far() {
+    let lines = "x be 1\nwrite x\nwrite +\nx be x plus 1\n:list";
+    let (answers, errors, _) = session(lines, "");
+    // The typo isn't in it
+    assert_eq!(answers, ["1 | x be 1", "2 | write x", "3 | x be x plus 1"]);
+    assert!(errors.contains("TYPO"), "{}", errors);
+
+    // Numbers line up once there are ten lines
+    let lines = "write 1\n".repeat(10) + ":list";
+    let (answers, _, _) = session(&lines, "");
+    assert_eq!(answers[0], " 1 | write 1");
+    assert_eq!(answers[9], "10 | write 1");
+}
+
+#[test]
+fn vars_shows_every_variable_in_order() {
+    let (answers, _, _) = session("b be \"two\"\na be 1\nc be no\n:vars", "");
+    assert_eq!(answers, ["a be 1", "b be \"two\"", "c be no"]);
+    let (answers, _, _) = session("a be 1\n:reset\n:vars\n:list", "");
+    assert!(answers.is_empty(), "{:?}", answers);
+}
+
+#[test]
+fn run_goes_back_into_earlier_lines() {
+    let lines = "x be 1\nwrite x\nx be x plus 1\nrun 2 to 3\nrun 2 to 3 repeat 2\n:vars";
+    let (answers, errors, written) = session(lines, "");
+    assert_eq!(written, "1\n2\n3\n4\n");
+    assert_eq!(answers, ["x be 5"]);
+    assert_eq!(errors, "");
+
+    // Lines that aren't there yet can't be run
+    let (_, errors, _) = session("write 1\nrun 1 to 5", "");
+    assert!(errors.contains("INVALID LINE RANGE"), "{}", errors);
+}
+
+#[test]
+fn load_replaces_the_program_and_runs_it() {
+    let path = std::env::temp_dir().join(format!("nooblang-repl-{}.noob", std::process::id()));
+    std::fs::write(&path, "y be 2\nwrite y\ny be y times 3").unwrap();
+
+    let lines = format!("x be 1\n:load {}\n:list\n:vars\nrun 2\n", path.display());
+    let (answers, errors, written) = session(&lines, "");
+    std::fs::remove_file(&path).unwrap();
+    // x is gone with the rest of the old program
+    assert_eq!(answers, ["1 | y be 2", "2 | write y", "3 | y be y times 3", "y be 6"]);
+    assert_eq!(written, "2\n6\n");
+    assert_eq!(errors, "");
+
+    let (_, errors, _) = session(":load /no/such/file.noob\n", "");
+    assert!(errors.contains("Failed to read file"), "{}", errors);
+}
+
+#[test]
+fn quit_stops_and_anything_else_is_unknown() {
+    let (answers, errors, written) = session(":what\n:help\n:quit\nwrite 1", "");
+    assert_eq!(errors, "WHAT IS :what? TRY :help\n");
+    assert_eq!(answers[0], ":list         show the program so far");
+    assert!(written.is_empty());
+}
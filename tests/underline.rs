@@ -0,0 +1,85 @@
+use nooblang_rust::{eval::Buffer, Eval, Reader};
+
+// The line shown with the error and the marks under it, without the
+// "--> " and the four spaces in front
+fn shown(e: String) -> (String, String) {
+    let lines: Vec<&str> = e.lines().collect();
+    let n = lines.len();
+    let source = lines[n - 2].strip_prefix("--> ").unwrap();
+    let marks = lines[n - 1].strip_prefix("    ").unwrap();
+    (source.to_string(), marks.to_string())
+}
+
+fn typo(source: &str) -> (String, String) {
+    let mut reader = Reader::from(source);
+    shown(nooblang_rust::parse(&mut reader).unwrap_err().to_string())
+}
+
+fn error(source: &str) -> (String, String) {
+    let mut reader = Reader::from(source);
+    let ast = nooblang_rust::parse(&mut reader).unwrap();
+    let mut io = Buffer::new("");
+    let mut eval = Eval::new(&ast, &reader);
+    eval.set_io(Box::new(&mut io));
+    shown(eval.run_prog().unwrap_err().to_string())
+}
+
+#[test]
+fn marks_go_under_the_whole_range() {
+    assert_eq!(error("write 1 plus yes").1, "      ^^^^^^^^^^-- LOOK");
+    assert_eq!(error("write x").1, "      ^-- LOOK");
+    assert_eq!(typo("write 1 2 3").1, "        ^^^-- SEE");
+    // Running lines that aren't there points at all of them
+    assert_eq!(error("run 1 to 99").1, "    ^^^^^^^-- LOOK");
+}
+
+#[test]
+fn tabs_before_are_copied() {
+    let (source, marks) = error("\twrite 1 plus yes");
+    assert_eq!(source, "\twrite 1 plus yes");
+    assert_eq!(marks, "\t      ^^^^^^^^^^-- LOOK");
+
+    // A tab in the range is one mark, there's nothing to copy it to
+    let (_, marks) = error("write\t1\tplus\t\"a\"\tminus 1");
+    assert_eq!(marks, "     \t^^^^^^^^^^-- LOOK");
+}
+
+#[test]
+fn letters_that_are_more_than_one_byte_are_one_column() {
+    let (_, marks) = error("write \"héllo\" minus x");
+    assert_eq!(marks, "                    ^-- LOOK");
+    let (_, marks) = typo("é be 1 +");
+    assert_eq!(marks, "       ^-- SEE");
+}
+
+#[test]
+fn wide_letters_are_two_columns() {
+    // Before the range
+    let (_, marks) = error("write \"日本\" minus x");
+    assert_eq!(marks, "                   ^-- LOOK");
+    let (_, marks) = error("write \"🙂\" minus x");
+    assert_eq!(marks, "                 ^-- LOOK");
+    // In it
+    let (_, marks) = error("write \"日本\" plus 1");
+    assert_eq!(marks, "      ^^^^^^^^^^^^^-- LOOK");
+    let (_, marks) = error("write \"🙂\" minus 1");
+    assert_eq!(marks, "      ^^^^^^^^^^^^-- LOOK");
+}
+
+#[test]
+fn accents_on_their_own_take_no_room() {
+    // e and a combining acute accent
+    let (_, marks) = error("write \"e\u{301}\" minus x");
+    assert_eq!(marks, "                ^-- LOOK");
+}
+
+#[test]
+fn ranges_past_the_line_stop_at_its_end() {
+    // Text that never ends, marked to the end of its first line
+    let (source, marks) = typo("x be \"\"\"日本\nstill going");
+    assert_eq!(source, "x be \"\"\"日本");
+    assert_eq!(marks, "     ^^^-- SEE");
+    let (source, marks) = typo("write \"日本");
+    assert_eq!(source, "write \"日本");
+    assert_eq!(marks, "      ^^^^^-- SEE");
+}